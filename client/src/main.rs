@@ -13,7 +13,7 @@ use anchor_lang::prelude::*;
 use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token::id;
 mod utils;
-use crate::utils::print_balances;
+use crate::utils::{print_balances, print_offers, print_stats};
 
 declare_program!(solswap);
 
@@ -31,6 +31,9 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     const USDC_PUBKEY: Pubkey = pubkey!("Gh9ZwEmdLJ8DscKNTkTqPbNwLNNBjuSzaG9Vp2KGtKJr");
     const WSOL_PUBKEY: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 
+    // Id our order management system tags the example offer with
+    const CLIENT_ORDER_ID: u128 = 1;
+
     let token_mint_in = WSOL_PUBKEY;
     let token_mint_out = USDC_PUBKEY;
     let token_program_id = id();
@@ -78,6 +81,32 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         &program.id(),
     );
 
+    // Derive the size limits PDA for the deposited mint
+    let (mint_limits_pda, _mint_limits_bump) =
+        Pubkey::find_program_address(&[b"mint_limits", token_mint_in.as_ref()], &program.id());
+
+    // Derive the statistics PDA for this pair
+    let (stats_pda, _stats_bump) = Pubkey::find_program_address(
+        &[b"stats", token_mint_in.as_ref(), token_mint_out.as_ref()],
+        &program.id(),
+    );
+
+    // Derive the circuit breaker PDA, shared by both directions of the pair
+    let (breaker_pda, _breaker_bump) = Pubkey::find_program_address(
+        &[
+            b"breaker",
+            token_mint_in.min(token_mint_out).as_ref(),
+            token_mint_in.max(token_mint_out).as_ref(),
+        ],
+        &program.id(),
+    );
+
+    // Derive the volume tracking PDAs used for fee tiers
+    let (taker_trader_pda, _taker_trader_bump) =
+        Pubkey::find_program_address(&[b"trader", taker.pubkey().as_ref()], &program.id());
+    let (depositor_trader_pda, _depositor_trader_bump) =
+        Pubkey::find_program_address(&[b"trader", depositor.pubkey().as_ref()], &program.id());
+
     // Derive vault PDA (associated token account for offer PDA)
     let vault_pda =
         get_associated_token_address_with_program_id(&offer_pda, &token_mint_in, &token_program_id);
@@ -104,11 +133,16 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         .request()
         .accounts(accounts::CreateOffer {
             signer: depositor.pubkey(),
+            depositor: depositor.pubkey(),
+            proceeds_recipient: None,
+            delegate: None,
             offer: offer_pda,
             config: config_pda,
             token_mint_in,
             token_mint_out,
+            mint_limits: mint_limits_pda,
             vault: vault_pda,
+            stats: stats_pda,
             user_token_account,
             system_program: Pubkey::new_from_array(solana_system_interface::program::ID.to_bytes()),
             token_program: token_program_id,
@@ -116,6 +150,11 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         })
         .args(args::CreateOffer {
             amount: (LAMPORTS_PER_SOL as f64 * 0.05) as u64, // Offer 0.05 SOL
+            auction: None,
+            trigger: None,
+            dca: None,
+            client_order_id: CLIENT_ORDER_ID,
+            memo: Some("example offer".to_string()),
         })
         .signer(&depositor)
         .send();
@@ -124,6 +163,7 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     match create_offer_sig {
         Ok(sig) => {
             println!("\nOffer created with signature: {:?}", sig);
+            print_offers(&program, Some(CLIENT_ORDER_ID), "Offers For Our Order Id");
         }
         Err(e) => {
             let raw = format!("{e}");
@@ -162,19 +202,40 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         .accounts(accounts::TakeOffer {
             taker: taker.pubkey(),
             depositor: depositor.pubkey(),
+            config: config_pda,
             token_mint_in,
             token_mint_out,
+            recipient: taker.pubkey(),
             taker_token_in_ata,
             taker_token_out_ata,
+            proceeds_owner: depositor.pubkey(),
             depositor_receive_ata,
             offer: offer_pda,
             vault: vault_pda,
+            stats: stats_pda,
+            breaker: breaker_pda,
+            taker_trader: taker_trader_pda,
+            depositor_trader: depositor_trader_pda,
             price_update: PRICE_FEED_ACCOUNT,
+            referrer_token_account: None,
+            fee_token_account: None,
+            gate_token_account: None,
+            position_token_account: None,
+            vesting_escrow: None,
+            vesting_vault: None,
+            reverse_offer: None,
+            reverse_vault: None,
+            reverse_stats: None,
+            reverse_mint_limits: None,
             associated_token_program: spl_associated_token_account::id(),
             system_program: Pubkey::new_from_array(solana_system_interface::program::ID.to_bytes()),
             token_program: token_program_id,
         })
-        .args(args::TakeOffer {})
+        .args(args::TakeOffer {
+            referral_bps: 0,
+            proof: vec![],
+            expected_vesting: None,
+        })
         .signer(&taker)
         .payer(Rc::clone(&taker))
         .send();
@@ -191,6 +252,12 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
                 "Balances After Taking Offer",
             );
             println!("\nOffer taken with signature: {:?}", sig);
+            print_stats(
+                &program,
+                &token_mint_in,
+                &token_mint_out,
+                "Pair Statistics After Taking Offer",
+            );
         }
         Err(e) => {
             let raw = format!("{e}");
@@ -1,9 +1,21 @@
+use std::ops::Deref;
+
 use anchor_client::{
-    solana_client::rpc_client::RpcClient, solana_sdk::commitment_config::CommitmentConfig,
+    solana_client::{
+        rpc_client::RpcClient,
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::{commitment_config::CommitmentConfig, signer::Signer},
+    Program,
 };
 use anchor_lang::prelude::Pubkey;
 use spl_associated_token_account::get_associated_token_address_with_program_id;
 
+use crate::solswap::{
+    accounts::{Offer, Stats},
+    constants::OFFER_CLIENT_ORDER_ID_OFFSET,
+};
+
 // Helper function to print balances in a table format
 pub fn print_balances(
     depositor: &Pubkey,
@@ -86,3 +98,73 @@ pub fn print_spl_balance(owner: &Pubkey, mint: &Pubkey, token_program_id: &Pubke
 
     println!("{:<20} | {} | {}", label, ata, balance_str);
 }
+
+// Print the on-chain statistics for offers selling `token_mint_in` for `token_mint_out`
+pub fn print_stats<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    token_mint_in: &Pubkey,
+    token_mint_out: &Pubkey,
+    title: &str,
+) {
+    let (stats_pda, _stats_bump) = Pubkey::find_program_address(
+        &[b"stats", token_mint_in.as_ref(), token_mint_out.as_ref()],
+        &program.id(),
+    );
+
+    println!("\n=== {} ===", title);
+    let stats = match program.account::<Stats>(stats_pda) {
+        Ok(stats) => stats,
+        Err(_) => {
+            println!("No stats recorded yet for {}", stats_pda);
+            return;
+        }
+    };
+
+    println!("{:<20} | {}", "Offers created", stats.offers_created);
+    println!("{:<20} | {}", "Offers filled", stats.offers_filled);
+    println!("{:<20} | {}", "Offers cancelled", stats.offers_cancelled);
+    println!("{:<20} | {}", "Volume in", stats.volume_in);
+    println!("{:<20} | {}", "Volume out", stats.volume_out);
+    println!("{:<20} | {}", "Escrowed", stats.escrowed);
+    println!("{:<20} | {}", "Last fill price", stats.last_fill_price);
+    println!(
+        "{:<20} | {}",
+        "Last fill timestamp", stats.last_fill_timestamp
+    );
+}
+
+// Print open offers, optionally only those tagged with `client_order_id`
+pub fn print_offers<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    client_order_id: Option<u128>,
+    title: &str,
+) {
+    let filters = match client_order_id {
+        Some(id) => vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            OFFER_CLIENT_ORDER_ID_OFFSET as usize,
+            &id.to_le_bytes(),
+        ))],
+        None => vec![],
+    };
+
+    println!("\n=== {} ===", title);
+    let offers = match program.accounts::<Offer>(filters) {
+        Ok(offers) => offers,
+        Err(e) => {
+            println!("Failed to list offers: {}", e);
+            return;
+        }
+    };
+
+    println!(
+        "{:<44} | {:<20} | {:<12} | Memo",
+        "Offer", "Client order id", "Amount"
+    );
+    println!("{}", "-".repeat(100));
+    for (address, offer) in offers {
+        println!(
+            "{:<44} | {:<20} | {:<12} | {}",
+            address, offer.client_order_id, offer.deposited_amount, offer.memo
+        );
+    }
+}
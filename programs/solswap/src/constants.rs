@@ -3,3 +3,13 @@ use anchor_lang::prelude::*;
 #[constant]
 pub const FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
 pub const MAXIMUM_AGE: u64 = 1000;
+pub const WSOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const QUOTE_DOMAIN: &[u8] = b"solswap:quote";
+pub const NONCES_PER_PAGE: u64 = 1024;
+/// Slots a take commitment must wait before it can be revealed
+pub const REVEAL_DELAY_SLOTS: u64 = 2;
+/// Slots after the delay during which a commitment can be revealed
+pub const REVEAL_WINDOW_SLOTS: u64 = 150;
+/// Longest vesting schedule an offer can set, in seconds
+pub const MAX_VESTING_DURATION: i64 = 4 * 365 * 86_400;
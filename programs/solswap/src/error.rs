@@ -13,4 +13,187 @@ pub enum ErrorCode {
 
     #[msg("Token in and token out cannot be the same")]
     SameToken,
+
+    #[msg("Auction parameters are invalid")]
+    InvalidAuction,
+
+    #[msg("Price must be greater than zero")]
+    InvalidPrice,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("Quote has expired")]
+    QuoteExpired,
+
+    #[msg("Quote does not match the supplied accounts")]
+    QuoteMismatch,
+
+    #[msg("Quote nonce has already been used")]
+    QuoteNonceUsed,
+
+    #[msg("Missing or invalid ed25519 signature for quote")]
+    InvalidQuoteSignature,
+
+    #[msg("Remaining accounts must come in groups of offer, vault and depositor ATA")]
+    InvalidBatchAccounts,
+
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+
+    #[msg("Referral bps exceed the configured maximum")]
+    InvalidReferralBps,
+
+    #[msg("Amount is below the minimum offer size for this mint")]
+    AmountTooSmall,
+
+    #[msg("Amount is above the maximum offer size for this mint")]
+    AmountTooLarge,
+
+    #[msg("Minimum must be non-zero and not exceed the maximum")]
+    InvalidMintLimits,
+
+    #[msg("Callback program cannot be this program")]
+    InvalidCallbackProgram,
+
+    #[msg("Depositor was not paid the full price by the flash take callback")]
+    FlashTakeUnderpaid,
+
+    #[msg("Offer has no trigger")]
+    NotATriggerOffer,
+
+    #[msg("Trigger has already been armed")]
+    TriggerAlreadyArmed,
+
+    #[msg("Oracle price has not crossed the trigger threshold")]
+    TriggerNotMet,
+
+    #[msg("Offer is not fillable at this time or price")]
+    OfferNotFillable,
+
+    #[msg("DCA parameters are invalid")]
+    InvalidDca,
+
+    #[msg("Delegate permissions or expiry are invalid")]
+    InvalidDelegate,
+
+    #[msg("Delegate has expired or lacks the required permission")]
+    DelegateNotPermitted,
+
+    #[msg("Account is not a legacy account of the expected type")]
+    InvalidLegacyAccount,
+
+    #[msg("Account already uses the current layout")]
+    AlreadyMigrated,
+
+    #[msg("Circuit breaker parameters are invalid")]
+    InvalidCircuitBreaker,
+
+    #[msg("Oracle price moved too far from the last fill")]
+    CircuitBreakerPriceMove,
+
+    #[msg("Pair notional limit for this window reached")]
+    CircuitBreakerNotional,
+
+    #[msg("Memo is too long")]
+    MemoTooLong,
+
+    #[msg("Price is above the taker's maximum")]
+    PriceAboveMax,
+
+    #[msg("Commitment cannot be revealed yet")]
+    RevealTooEarly,
+
+    #[msg("Commitment reveal window has passed")]
+    CommitmentExpired,
+
+    #[msg("Revealed take does not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Commitment can still be revealed")]
+    CommitmentNotExpired,
+
+    #[msg("Fee tiers are invalid")]
+    InvalidFeeTiers,
+
+    #[msg("Fee token account is required when a protocol fee is due")]
+    MissingFeeAccount,
+
+    #[msg("Barter offers cannot use auctions, triggers or DCA, or be amended")]
+    InvalidBarter,
+
+    #[msg("Offer is not a barter offer")]
+    NotABarterOffer,
+
+    #[msg("Basket legs are invalid")]
+    InvalidBasket,
+
+    #[msg("Vesting schedule is invalid")]
+    InvalidVesting,
+
+    #[msg("Vesting offers can only be taken with take_offer or reveal_take")]
+    VestingNotSupported,
+
+    #[msg("Vesting escrow accounts are required for this offer")]
+    MissingVestingAccounts,
+
+    #[msg("Nothing has vested yet")]
+    NothingVested,
+
+    #[msg("Offer already has a position NFT")]
+    AlreadyTokenized,
+
+    #[msg("Signer or account is not the holder of the offer's position NFT")]
+    NotPositionHolder,
+
+    #[msg("Not supported for offers with a position NFT")]
+    OfferTokenized,
+
+    #[msg("Grid parameters are invalid")]
+    InvalidGrid,
+
+    #[msg("Grid offers can only be taken with take_offer or reveal_take")]
+    GridNotSupported,
+
+    #[msg("Reverse offer accounts are required for this grid offer")]
+    MissingGridAccounts,
+
+    #[msg("Depositor already has a non-grid offer on the reverse side")]
+    GridSlotTaken,
+
+    #[msg("Ladder parameters are invalid")]
+    InvalidLadder,
+
+    #[msg("Taker is not on the offer's allowlist")]
+    TakerNotAllowed,
+
+    #[msg("Allowlisted offers can only be taken with take_offer, reveal_take or take_barter")]
+    AllowlistNotSupported,
+
+    #[msg("Token gate requires a positive minimum amount")]
+    InvalidTokenGate,
+
+    #[msg("Taker does not hold the tokens required by the offer's gate")]
+    TakerNotGated,
+
+    #[msg("Token-gated offers can only be taken with take_offer, reveal_take or take_barter")]
+    TokenGateNotSupported,
+
+    #[msg("Barter offer no longer matches the amounts the taker expected")]
+    BarterTermsChanged,
+
+    #[msg("Offer vesting schedule differs from the one the taker expected")]
+    VestingMismatch,
+
+    #[msg("Depositor already has an open offer for this mint")]
+    OfferAlreadyExists,
+
+    #[msg("Allowlisted and token-gated offers only pay out to the taker")]
+    RecipientNotTaker,
+
+    #[msg("DCA tranches can only be filled whole")]
+    PartialTranche,
+
+    #[msg("Only the depositor can set a price other than the oracle's")]
+    ManagerPricing,
 }
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct OfferCreated {
+    pub offer: Pubkey,
+    pub depositor: Pubkey,
+    pub token_mint_in: Pubkey,
+    pub token_mint_out: Pubkey,
+    pub amount: u64,
+    pub client_order_id: u128,
+    pub memo: String,
+}
+
+#[event]
+pub struct OfferTaken {
+    pub offer: Pubkey,
+    pub depositor: Pubkey,
+    pub taker: Pubkey,
+    pub token_mint_in: Pubkey,
+    pub token_mint_out: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Owner of the referrer token account, if any
+    pub referrer: Option<Pubkey>,
+    pub referral_amount: u64,
+    pub client_order_id: u128,
+}
+
+#[event]
+pub struct TriggerArmed {
+    pub offer: Pubkey,
+    pub keeper: Pubkey,
+    pub price: i64,
+    pub bounty: u64,
+}
+
+#[event]
+pub struct OfferCancelled {
+    pub offer: Pubkey,
+    pub depositor: Pubkey,
+    pub authority: Pubkey,
+    pub amount_returned: u64,
+    pub client_order_id: u128,
+}
+
+#[event]
+pub struct BasketTaken {
+    pub basket: Pubkey,
+    pub depositor: Pubkey,
+    pub taker: Pubkey,
+    pub token_mint_out: Pubkey,
+    /// Summed oracle value of the legs, in USD with 8 decimals
+    pub value_usd: u128,
+    pub amount_out: u64,
+}
+
+#[event]
+pub struct PositionMinted {
+    pub offer: Pubkey,
+    pub depositor: Pubkey,
+    pub position_mint: Pubkey,
+}
+
+#[event]
+pub struct LadderLevelTaken {
+    pub ladder: Pubkey,
+    pub depositor: Pubkey,
+    pub taker: Pubkey,
+    pub level: u8,
+    pub price: i64,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::{check_offer_authority, error::ErrorCode, Delegate, Offer, TokenGate, DELEGATE_AMEND};
+
+#[derive(Accounts)]
+pub struct SetTakerAllowlist<'info> {
+    /// Depositor, or a manager holding a delegation from them
+    pub authority: Signer<'info>,
+
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"delegate", depositor.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    #[account(
+        mut,
+        has_one = depositor,
+        seeds = [offer.token_mint_in.as_ref(), depositor.key().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+/// Restricts the offer to takers in the Merkle tree with `root`, or opens it
+/// to everyone again.
+pub fn process_set_taker_allowlist(
+    ctx: Context<SetTakerAllowlist>,
+    root: Option<[u8; 32]>,
+) -> Result<()> {
+    // The depositor could otherwise block fills of a position they sold
+    require!(
+        ctx.accounts.offer.position_mint.is_none(),
+        ErrorCode::OfferTokenized
+    );
+    check_offer_authority(
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.accounts.delegate.as_deref(),
+        DELEGATE_AMEND,
+    )?;
+
+    ctx.accounts.offer.taker_merkle_root = root;
+    ctx.accounts.offer.updated_slot = Clock::get()?.slot;
+    Ok(())
+}
+
+/// Restricts the offer to takers holding `gate.min_amount` of `gate.mint`, or
+/// lifts the restriction. Uses the same accounts as `set_taker_allowlist`.
+pub fn process_set_taker_gate(
+    ctx: Context<SetTakerAllowlist>,
+    gate: Option<TokenGate>,
+) -> Result<()> {
+    // The depositor could otherwise block fills of a position they sold
+    require!(
+        ctx.accounts.offer.position_mint.is_none(),
+        ErrorCode::OfferTokenized
+    );
+    check_offer_authority(
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.accounts.delegate.as_deref(),
+        DELEGATE_AMEND,
+    )?;
+
+    if let Some(gate) = gate {
+        require!(gate.min_amount > 0, ErrorCode::InvalidTokenGate);
+    }
+
+    ctx.accounts.offer.taker_gate = gate;
+    ctx.accounts.offer.updated_slot = Clock::get()?.slot;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    check_offer_authority, error::ErrorCode, Delegate, MintLimits, Offer, Stats, DELEGATE_AMEND,
+};
+
+#[derive(Accounts)]
+pub struct AmendOffer<'info> {
+    /// Depositor, or a manager holding a delegation from them
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"delegate", depositor.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: limits PDA for `token_mint_in`; may be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub mint_limits: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = depositor,
+        has_one = token_mint_in,
+        seeds = [token_mint_in.key().as_ref(), depositor.key().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", offer.token_mint_in.as_ref(), offer.token_mint_out.as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Resizes an open offer to `new_amount`, topping up from or refunding to the
+/// depositor's token account.
+pub fn process_amend_offer(ctx: Context<AmendOffer>, new_amount: u64) -> Result<()> {
+    require!(new_amount > 0, ErrorCode::AmountZero);
+    // Refunds would go to the depositor rather than the position holder
+    require!(
+        ctx.accounts.offer.position_mint.is_none(),
+        ErrorCode::OfferTokenized
+    );
+    // The barter price is fixed for the whole deposit
+    require!(
+        ctx.accounts.offer.barter_amount_out.is_none(),
+        ErrorCode::InvalidBarter
+    );
+
+    let authority = ctx.accounts.authority.key();
+    let depositor = ctx.accounts.depositor.key();
+    check_offer_authority(
+        &authority,
+        &depositor,
+        ctx.accounts.delegate.as_deref(),
+        DELEGATE_AMEND,
+    )?;
+
+    if let Some(limits) = MintLimits::load(&ctx.accounts.mint_limits)? {
+        limits.check_deposit(new_amount)?;
+    }
+
+    let current = ctx.accounts.offer.deposited_amount;
+    let decimals = ctx.accounts.token_mint_in.decimals;
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+
+    if new_amount > current {
+        let top_up = new_amount - current;
+        let (authority_info, delegate_bump) = match &ctx.accounts.delegate {
+            // Managers top up through the approval the depositor granted the delegate PDA
+            Some(delegate) if authority != depositor => {
+                (delegate.to_account_info(), Some(delegate.bump))
+            }
+            _ => (ctx.accounts.authority.to_account_info(), None),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.depositor_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: authority_info,
+            mint: ctx.accounts.token_mint_in.to_account_info(),
+        };
+
+        match delegate_bump {
+            Some(bump) => {
+                let delegate_seeds = &[
+                    b"delegate".as_ref(),
+                    depositor.as_ref(),
+                    authority.as_ref(),
+                    &[bump],
+                ];
+                let signer_seeds = &[&delegate_seeds[..]];
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+                transfer_checked(cpi_ctx, top_up, decimals)?;
+            }
+            None => {
+                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+                transfer_checked(cpi_ctx, top_up, decimals)?;
+            }
+        }
+    } else if new_amount < current {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.depositor_token_account.to_account_info(),
+            authority: ctx.accounts.offer.to_account_info(),
+            mint: ctx.accounts.token_mint_in.to_account_info(),
+        };
+
+        let token_mint_in = ctx.accounts.token_mint_in.key();
+        let offer_seeds = &[
+            token_mint_in.as_ref(),
+            depositor.as_ref(),
+            &[ctx.accounts.offer.bump],
+        ];
+        let signer_seeds = &[&offer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, current - new_amount, decimals)?;
+    }
+
+    ctx.accounts.offer.deposited_amount = new_amount;
+    ctx.accounts.offer.updated_slot = Clock::get()?.slot;
+
+    let offer = &ctx.accounts.offer;
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(offer.token_mint_in, offer.token_mint_out, ctx.bumps.stats);
+    stats.record_amended(current, new_amount);
+
+    msg!("Offer amended: {} -> {}", current, new_amount);
+
+    Ok(())
+}
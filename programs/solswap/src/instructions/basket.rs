@@ -0,0 +1,489 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{
+        create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+    },
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use super::pay_protocol_fee;
+use crate::{
+    amount_for_usd, error::ErrorCode, usd_value, BasketLeg, BasketOffer, BasketTaken, Config,
+    PriceFeed, TraderAccount, MAXIMUM_AGE, MAX_BASKET_LEGS, USDC_DECIMALS,
+};
+
+/// Accounts each leg contributes to `remaining_accounts` when creating a
+/// basket, in order: mint, depositor token account, vault.
+pub const BASKET_CREATE_ACCOUNTS_PER_LEG: usize = 3;
+
+/// Accounts each leg contributes to `remaining_accounts` when taking a
+/// basket, in order: mint, vault, taker token account, depositor token
+/// account, price feed, price update.
+pub const BASKET_TAKE_ACCOUNTS_PER_LEG: usize = 6;
+
+/// Accounts each leg contributes to `remaining_accounts` when cancelling a
+/// basket, in order: mint, vault, depositor token account.
+pub const BASKET_CANCEL_ACCOUNTS_PER_LEG: usize = 3;
+
+#[derive(Accounts)]
+#[instruction(basket_id: u64)]
+pub struct CreateBasketOffer<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + BasketOffer::INIT_SPACE,
+        seeds = [b"basket", depositor.key().as_ref(), basket_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub basket: Account<'info, BasketOffer>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Escrows `amounts[i]` of each leg's mint in a vault owned by the basket PDA.
+pub fn process_create_basket_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateBasketOffer<'info>>,
+    basket_id: u64,
+    amounts: Vec<u64>,
+) -> Result<()> {
+    require!(
+        !amounts.is_empty() && amounts.len() <= MAX_BASKET_LEGS,
+        ErrorCode::InvalidBasket
+    );
+    require!(
+        ctx.remaining_accounts.len() == amounts.len() * BASKET_CREATE_ACCOUNTS_PER_LEG,
+        ErrorCode::InvalidBatchAccounts
+    );
+
+    let basket_key = ctx.accounts.basket.key();
+    let token_program = &ctx.accounts.token_program;
+    let mut legs = [BasketLeg::default(); MAX_BASKET_LEGS];
+
+    for (i, (accounts, &amount)) in ctx
+        .remaining_accounts
+        .chunks_exact(BASKET_CREATE_ACCOUNTS_PER_LEG)
+        .zip(amounts.iter())
+        .enumerate()
+    {
+        let [mint_info, depositor_token_info, vault_info] = accounts else {
+            unreachable!()
+        };
+        require!(amount > 0, ErrorCode::AmountZero);
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        require!(
+            mint.key() != ctx.accounts.token_mint_out.key()
+                && legs[..i].iter().all(|leg| leg.mint != mint.key()),
+            ErrorCode::InvalidBasket
+        );
+        require_keys_eq!(
+            vault_info.key(),
+            get_associated_token_address_with_program_id(
+                &basket_key,
+                &mint.key(),
+                &token_program.key()
+            ),
+            ErrorCode::InvalidBatchAccounts
+        );
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            Create {
+                payer: ctx.accounts.depositor.to_account_info(),
+                associated_token: vault_info.clone(),
+                authority: ctx.accounts.basket.to_account_info(),
+                mint: mint_info.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+        );
+        create_idempotent(cpi_ctx)?;
+
+        let cpi_accounts = TransferChecked {
+            from: depositor_token_info.clone(),
+            to: vault_info.clone(),
+            authority: ctx.accounts.depositor.to_account_info(),
+            mint: mint_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_ctx, amount, mint.decimals)?;
+
+        legs[i] = BasketLeg {
+            mint: mint.key(),
+            vault: vault_info.key(),
+            amount,
+        };
+    }
+
+    *ctx.accounts.basket = BasketOffer {
+        depositor: ctx.accounts.depositor.key(),
+        basket_id,
+        token_mint_out: ctx.accounts.token_mint_out.key(),
+        legs,
+        leg_count: amounts.len() as u8,
+        bump: ctx.bumps.basket,
+    };
+
+    msg!("Basket created: {}", basket_key);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TakeBasketOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = taker,
+        has_one = depositor,
+        has_one = token_mint_out,
+        seeds = [b"basket", depositor.key().as_ref(), basket.basket_id.to_le_bytes().as_ref()],
+        bump = basket.bump
+    )]
+    pub basket: Account<'info, BasketOffer>,
+
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"price_feed", token_mint_out.key().as_ref()],
+        bump = out_price_feed.bump
+    )]
+    pub out_price_feed: Account<'info, PriceFeed>,
+
+    pub out_price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_out_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_receive_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", taker.key().as_ref()],
+        bump
+    )]
+    pub taker_trader: Box<Account<'info, TraderAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", depositor.key().as_ref()],
+        bump
+    )]
+    pub depositor_trader: Box<Account<'info, TraderAccount>>,
+
+    /// Admin token account receiving the protocol share of the taker fee
+    #[account(
+        mut,
+        token::mint = token_mint_out,
+        token::token_program = token_program,
+        constraint = fee_token_account.owner == config.admin @ ErrorCode::Unauthorized,
+    )]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Sends every leg to the taker in exchange for `token_mint_out` worth the
+/// summed oracle value of the legs, plus the taker's fee tier, failing if
+/// that exceeds `max_amount_out`. The value counts as both sides' volume.
+/// Tokens sent to a vault on top of its leg and the vault rent go back to the
+/// depositor.
+///
+/// Baskets span several mints valued by their own feeds, so the per-pair
+/// circuit breaker and Stats do not apply. They are taken whole, leaving no
+/// residual for MintLimits to bound.
+pub fn process_take_basket_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeBasketOffer<'info>>,
+    max_amount_out: u64,
+) -> Result<()> {
+    let basket = &ctx.accounts.basket;
+    let legs = basket.legs();
+    require!(
+        ctx.remaining_accounts.len() == legs.len() * BASKET_TAKE_ACCOUNTS_PER_LEG,
+        ErrorCode::InvalidBatchAccounts
+    );
+
+    let clock = Clock::get()?;
+    let token_program = &ctx.accounts.token_program;
+    let depositor = basket.depositor;
+    let basket_id = basket.basket_id.to_le_bytes();
+    let basket_seeds = &[
+        b"basket".as_ref(),
+        depositor.as_ref(),
+        basket_id.as_ref(),
+        &[basket.bump],
+    ];
+    let signer_seeds = &[&basket_seeds[..]];
+
+    let mut value: u128 = 0;
+
+    for (leg, accounts) in legs.iter().zip(
+        ctx.remaining_accounts
+            .chunks_exact(BASKET_TAKE_ACCOUNTS_PER_LEG),
+    ) {
+        let [mint_info, vault_info, taker_token_info, depositor_token_info, price_feed_info, price_update_info] =
+            accounts
+        else {
+            unreachable!()
+        };
+        require!(
+            mint_info.key() == leg.mint && vault_info.key() == leg.vault,
+            ErrorCode::InvalidBatchAccounts
+        );
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let price_feed = Account::<PriceFeed>::try_from(price_feed_info)?;
+        require_keys_eq!(price_feed.mint, leg.mint, ErrorCode::InvalidBatchAccounts);
+        let price = Account::<PriceUpdateV2>::try_from(price_update_info)?
+            .get_price_no_older_than(&clock, MAXIMUM_AGE, &price_feed.feed_id)?;
+
+        let leg_value = usd_value(leg.amount, mint.decimals, price.price, price.exponent)
+            .ok_or(ErrorCode::InvalidPrice)?;
+        value = value
+            .checked_add(leg_value)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let cpi_accounts = TransferChecked {
+            from: vault_info.clone(),
+            to: taker_token_info.clone(),
+            authority: basket.to_account_info(),
+            mint: mint_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, leg.amount, mint.decimals)?;
+
+        // Anyone can send tokens to the vault ATA; it must be empty to close
+        let excess = InterfaceAccount::<TokenAccount>::try_from(vault_info)?.amount;
+        if excess > 0 {
+            let depositor_token = InterfaceAccount::<TokenAccount>::try_from(depositor_token_info)?;
+            require!(
+                depositor_token.owner == depositor && depositor_token.mint == leg.mint,
+                ErrorCode::InvalidBatchAccounts
+            );
+            let cpi_accounts = TransferChecked {
+                from: vault_info.clone(),
+                to: depositor_token_info.clone(),
+                authority: basket.to_account_info(),
+                mint: mint_info.clone(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            );
+            transfer_checked(cpi_ctx, excess, mint.decimals)?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: vault_info.clone(),
+            destination: ctx.accounts.depositor.to_account_info(),
+            authority: basket.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        close_account(cpi_ctx)?;
+    }
+
+    let out_price = ctx.accounts.out_price_update.get_price_no_older_than(
+        &clock,
+        MAXIMUM_AGE,
+        &ctx.accounts.out_price_feed.feed_id,
+    )?;
+    let amount_out = amount_for_usd(
+        value,
+        ctx.accounts.token_mint_out.decimals,
+        out_price.price,
+        out_price.exponent,
+    )
+    .ok_or(ErrorCode::InvalidPrice)?;
+    require!(amount_out > 0, ErrorCode::InvalidPrice);
+
+    let now = clock.unix_timestamp;
+    ctx.accounts
+        .taker_trader
+        .init_if_empty(ctx.accounts.taker.key(), ctx.bumps.taker_trader);
+    ctx.accounts
+        .depositor_trader
+        .init_if_empty(ctx.accounts.depositor.key(), ctx.bumps.depositor_trader);
+    let fees = ctx.accounts.config.fee_rates(
+        ctx.accounts.taker_trader.rolling_volume(now),
+        ctx.accounts.depositor_trader.rolling_volume(now),
+    );
+    let (taker_fee, maker_rebate) = fees.fees_on(amount_out);
+    require!(
+        amount_out as u128 + taker_fee as u128 <= max_amount_out as u128,
+        ErrorCode::PriceAboveMax
+    );
+
+    msg!("Basket worth {} USD e-8 | {} out", value, amount_out);
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.taker_token_out_ata.to_account_info(),
+        to: ctx.accounts.depositor_receive_ata.to_account_info(),
+        authority: ctx.accounts.taker.to_account_info(),
+        mint: ctx.accounts.token_mint_out.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+    // The maker rebate is carved from the taker fee and paid with the price
+    transfer_checked(
+        cpi_ctx,
+        amount_out + maker_rebate,
+        ctx.accounts.token_mint_out.decimals,
+    )?;
+    pay_protocol_fee(
+        taker_fee - maker_rebate,
+        ctx.accounts.taker_token_out_ata.to_account_info(),
+        ctx.accounts.taker.to_account_info(),
+        ctx.accounts.fee_token_account.as_ref(),
+        &ctx.accounts.token_mint_out,
+        token_program,
+    )?;
+
+    emit!(BasketTaken {
+        basket: basket.key(),
+        depositor,
+        taker: ctx.accounts.taker.key(),
+        token_mint_out: basket.token_mint_out,
+        value_usd: value,
+        amount_out,
+    });
+
+    // Volume is counted in USDC, at a dollar each
+    let notional = amount_for_usd(value, USDC_DECIMALS, 1, 0).ok_or(ErrorCode::MathOverflow)?;
+    ctx.accounts.taker_trader.record_volume(notional, now);
+    ctx.accounts.depositor_trader.record_volume(notional, now);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelBasketOffer<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor,
+        seeds = [b"basket", depositor.key().as_ref(), basket.basket_id.to_le_bytes().as_ref()],
+        bump = basket.bump
+    )]
+    pub basket: Account<'info, BasketOffer>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Returns every leg to the depositor and closes the vaults and the basket.
+pub fn process_cancel_basket_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelBasketOffer<'info>>,
+) -> Result<()> {
+    let basket = &ctx.accounts.basket;
+    let legs = basket.legs();
+    require!(
+        ctx.remaining_accounts.len() == legs.len() * BASKET_CANCEL_ACCOUNTS_PER_LEG,
+        ErrorCode::InvalidBatchAccounts
+    );
+
+    let token_program = &ctx.accounts.token_program;
+    let depositor = basket.depositor;
+    let basket_id = basket.basket_id.to_le_bytes();
+    let basket_seeds = &[
+        b"basket".as_ref(),
+        depositor.as_ref(),
+        basket_id.as_ref(),
+        &[basket.bump],
+    ];
+    let signer_seeds = &[&basket_seeds[..]];
+
+    for (leg, accounts) in legs.iter().zip(
+        ctx.remaining_accounts
+            .chunks_exact(BASKET_CANCEL_ACCOUNTS_PER_LEG),
+    ) {
+        let [mint_info, vault_info, depositor_token_info] = accounts else {
+            unreachable!()
+        };
+        require!(
+            mint_info.key() == leg.mint && vault_info.key() == leg.vault,
+            ErrorCode::InvalidBatchAccounts
+        );
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        // Refund the whole balance, including anything sent to the vault on top of the leg
+        let amount = InterfaceAccount::<TokenAccount>::try_from(vault_info)?.amount;
+
+        let cpi_accounts = TransferChecked {
+            from: vault_info.clone(),
+            to: depositor_token_info.clone(),
+            authority: basket.to_account_info(),
+            mint: mint_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, amount, mint.decimals)?;
+
+        let cpi_accounts = CloseAccount {
+            account: vault_info.clone(),
+            destination: ctx.accounts.depositor.to_account_info(),
+            authority: basket.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        close_account(cpi_ctx)?;
+    }
+
+    msg!("Basket cancelled: {}", basket.key());
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{
+    check_offer_authority, error::ErrorCode, Delegate, Offer, OfferCancelled, Stats,
+    DELEGATE_CANCEL,
+};
+
+#[derive(Accounts)]
+pub struct CancelOffer<'info> {
+    /// Depositor, or a manager holding a delegation from them; the position
+    /// holder for tokenized offers
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"delegate", depositor.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor,
+        has_one = token_mint_in,
+        seeds = [token_mint_in.key().as_ref(), depositor.key().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", offer.token_mint_in.as_ref(), offer.token_mint_out.as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    /// CHECK: the depositor, or the holder of the offer's position NFT; checked in the handler
+    pub proceeds_owner: UncheckedAccount<'info>,
+
+    /// Holder's account of the position NFT, required for tokenized offers
+    pub position_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = proceeds_owner,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Returns the escrowed tokens to the depositor and closes the vault and offer.
+/// Proceeds always go to the depositor, even when a manager cancels. Tokenized
+/// offers can only be cancelled by the position holder, who gets the tokens.
+pub fn process_cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    let proceeds_owner = ctx.accounts.proceeds_owner.key();
+    offer.check_refund_owner(
+        &proceeds_owner,
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+
+    if offer.position_mint.is_some() {
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            proceeds_owner,
+            ErrorCode::NotPositionHolder
+        );
+    } else {
+        check_offer_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.depositor.key(),
+            ctx.accounts.delegate.as_deref(),
+            DELEGATE_CANCEL,
+        )?;
+    }
+
+    let token_mint_in = ctx.accounts.token_mint_in.key();
+    let depositor = ctx.accounts.depositor.key();
+    let offer_seeds = &[
+        token_mint_in.as_ref(),
+        depositor.as_ref(),
+        &[ctx.accounts.offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    let amount = ctx.accounts.vault.amount;
+    if amount > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.depositor_token_account.to_account_info(),
+            authority: ctx.accounts.offer.to_account_info(),
+            mint: ctx.accounts.token_mint_in.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint_in.decimals)?;
+    }
+
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.depositor.to_account_info(),
+        authority: ctx.accounts.offer.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    close_account(cpi_ctx)?;
+
+    let offer = &ctx.accounts.offer;
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(offer.token_mint_in, offer.token_mint_out, ctx.bumps.stats);
+    stats.record_cancelled(offer.deposited_amount);
+
+    emit!(OfferCancelled {
+        offer: ctx.accounts.offer.key(),
+        depositor,
+        authority: ctx.accounts.authority.key(),
+        amount_returned: amount,
+        client_order_id: offer.client_order_id,
+    });
+
+    Ok(())
+}
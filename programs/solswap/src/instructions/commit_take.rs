@@ -0,0 +1,189 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+// Glob import brings in the helpers Anchor generates for the nested `TakeOffer`
+use super::take_offer::*;
+use crate::{error::ErrorCode, MintLimits, Offer, TakeCommitment, VestingParams};
+
+#[derive(Accounts)]
+pub struct CommitTake<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        init,
+        payer = taker,
+        space = 8 + TakeCommitment::INIT_SPACE,
+        seeds = [b"commitment", offer.key().as_ref(), taker.key().as_ref()],
+        bump
+    )]
+    pub commitment: Account<'info, TakeCommitment>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Records the hash of a take and escrows `bond` lamports until it is revealed.
+pub fn process_commit_take(ctx: Context<CommitTake>, hash: [u8; 32], bond: u64) -> Result<()> {
+    require!(bond > 0, ErrorCode::AmountZero);
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.taker.to_account_info(),
+            to: ctx.accounts.commitment.to_account_info(),
+        },
+    );
+    transfer(cpi_ctx, bond)?;
+
+    *ctx.accounts.commitment = TakeCommitment {
+        taker: ctx.accounts.taker.key(),
+        offer: ctx.accounts.offer.key(),
+        depositor: ctx.accounts.offer.depositor,
+        offer_updated_slot: ctx.accounts.offer.updated_slot,
+        hash,
+        bond,
+        commit_slot: Clock::get()?.slot,
+        bump: ctx.bumps.commitment,
+    };
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevealTake<'info> {
+    pub take: TakeOffer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"commitment", take.offer.key().as_ref(), take.taker.key().as_ref()],
+        bump = commitment.bump
+    )]
+    pub commitment: Account<'info, TakeCommitment>,
+
+    /// CHECK: limits PDA for `token_mint_in`; may be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", take.token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub mint_limits: UncheckedAccount<'info>,
+}
+
+/// Executes a committed take at the price in effect now, as long as it is
+/// within `max_price` (in `token_mint_out` base units per whole
+/// `token_mint_in`, taker fee included), and refunds the bond to the taker.
+pub fn process_reveal_take(
+    ctx: Context<RevealTake>,
+    amount: u64,
+    max_price: u64,
+    salt: [u8; 32],
+    referral_bps: u16,
+    proof: Vec<[u8; 32]>,
+    expected_vesting: Option<VestingParams>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let commitment = &ctx.accounts.commitment;
+    require!(
+        clock.slot >= commitment.reveal_starts_at(),
+        ErrorCode::RevealTooEarly
+    );
+    require!(
+        clock.slot <= commitment.reveal_ends_at(),
+        ErrorCode::CommitmentExpired
+    );
+    require!(
+        TakeCommitment::hash_take(&commitment.offer, amount, max_price, &salt) == commitment.hash,
+        ErrorCode::CommitmentMismatch
+    );
+
+    let take = &mut ctx.accounts.take;
+    take.offer.check_vesting(expected_vesting)?;
+    take.offer.check_taker_allowed(take.taker.key, &proof)?;
+    take.offer
+        .check_taker_gate(take.taker.key, take.gate_token_account.as_deref())?;
+    take.offer
+        .check_recipient(take.taker.key, take.recipient.key)?;
+    let price = take.oracle_price(&clock)?;
+    let now = clock.unix_timestamp;
+
+    // Partial reveals must not leave a residual below the minimum offer size
+    if let Some(limits) = MintLimits::load(&ctx.accounts.mint_limits)? {
+        require!(
+            limits.max_fill(take.offer.deposited_amount, amount) == amount,
+            ErrorCode::AmountTooSmall
+        );
+    }
+    // A partial fill would lock the rest of the tranche until the next interval
+    if take.offer.dca.is_some() {
+        require!(
+            amount == take.offer.available_amount(now),
+            ErrorCode::PartialTranche
+        );
+    }
+
+    let unit_in = 10u128.pow(take.token_mint_in.decimals as u32);
+    let quote = take.fill(amount, price, now, referral_bps, &ctx.bumps.take)?;
+    let amount_paid = quote.amount_out as u128 + quote.taker_fee as u128;
+    require!(
+        amount_paid * unit_in <= max_price as u128 * amount as u128,
+        ErrorCode::PriceAboveMax
+    );
+
+    // Bond and rent go back to the taker
+    ctx.accounts
+        .commitment
+        .close(ctx.accounts.take.taker.to_account_info())?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ForfeitCommitment<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub taker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+
+    /// CHECK: the committed offer; may have been closed since the commitment
+    #[account(address = commitment.offer)]
+    pub offer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = taker,
+        has_one = taker,
+        has_one = depositor,
+        seeds = [b"commitment", commitment.offer.as_ref(), taker.key().as_ref()],
+        bump = commitment.bump
+    )]
+    pub commitment: Account<'info, TakeCommitment>,
+}
+
+/// Pays the bond of a commitment that was never revealed to the depositor;
+/// the rent goes back to the taker. If the depositor cancelled or changed the
+/// offer since the commitment, the bond goes back to the taker as well.
+pub fn process_forfeit_commitment(ctx: Context<ForfeitCommitment>) -> Result<()> {
+    let commitment = &ctx.accounts.commitment;
+    require!(
+        Clock::get()?.slot > commitment.reveal_ends_at(),
+        ErrorCode::CommitmentNotExpired
+    );
+
+    let offer_info = &ctx.accounts.offer;
+    let unchanged = offer_info.owner == &crate::ID
+        && Offer::try_deserialize(&mut &offer_info.try_borrow_data()?[..])
+            .is_ok_and(|offer| commitment.offer_unchanged(&offer));
+
+    // Otherwise the bond is returned with the rent when the commitment closes
+    if unchanged {
+        let bond = commitment.bond;
+        ctx.accounts.commitment.sub_lamports(bond)?;
+        ctx.accounts.depositor.add_lamports(bond)?;
+        msg!("Bond of {} forfeited to the depositor", bond);
+    }
+    Ok(())
+}
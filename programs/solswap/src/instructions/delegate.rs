@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Delegate, DELEGATE_AMEND, DELEGATE_CANCEL, DELEGATE_CREATE};
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub manager: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + Delegate::INIT_SPACE,
+        seeds = [b"delegate", depositor.key().as_ref(), manager.key().as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_set_delegate(ctx: Context<SetDelegate>, permissions: u8, expiry: i64) -> Result<()> {
+    let all = DELEGATE_CREATE | DELEGATE_AMEND | DELEGATE_CANCEL;
+    require!(
+        permissions != 0 && permissions & !all == 0,
+        ErrorCode::InvalidDelegate
+    );
+    require!(
+        expiry > Clock::get()?.unix_timestamp,
+        ErrorCode::InvalidDelegate
+    );
+
+    *ctx.accounts.delegate = Delegate {
+        depositor: ctx.accounts.depositor.key(),
+        manager: ctx.accounts.manager.key(),
+        permissions,
+        expiry,
+        bump: ctx.bumps.delegate,
+    };
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor,
+        seeds = [b"delegate", depositor.key().as_ref(), delegate.manager.as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+}
+
+pub fn process_revoke_delegate(_ctx: Context<RevokeDelegate>) -> Result<()> {
+    Ok(())
+}
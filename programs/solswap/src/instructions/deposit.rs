@@ -1,17 +1,34 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::Offer;
+use crate::{
+    check_offer_authority, Auction, AuctionParams, Dca, DcaParams, Delegate, MintLimits, Offer,
+    OfferCreated, Stats, Trigger, TriggerParams, DELEGATE_CREATE, MAX_MEMO_LEN,
+    OFFER_RESERVED_SPACE, OFFER_VERSION, WSOL_MINT,
+};
 use crate::{error::ErrorCode, Config};
 
 #[derive(Accounts)]
 pub struct CreateOffer<'info> {
+    /// Depositor, or a manager holding a delegation from them
     #[account(mut)]
     pub signer: Signer<'info>,
 
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"delegate", depositor.key().as_ref(), signer.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    /// CHECK: any wallet; paid by takers instead of the depositor when set; only the depositor can set it
+    pub proceeds_recipient: Option<UncheckedAccount<'info>>,
+
     #[account(
         seeds = [b"config"],
         bump
@@ -20,11 +37,18 @@ pub struct CreateOffer<'info> {
     pub token_mint_in: InterfaceAccount<'info, Mint>,
     pub token_mint_out: InterfaceAccount<'info, Mint>,
 
+    /// CHECK: limits PDA for `token_mint_in`; may be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub mint_limits: UncheckedAccount<'info>,
+
     #[account(
         init_if_needed,
         payer = signer,
         space = 8 + Offer::INIT_SPACE,
-        seeds = [token_mint_in.key().as_ref(),signer.key().as_ref()],
+        seeds = [token_mint_in.key().as_ref(),depositor.key().as_ref()],
         bump
     )]
     pub offer: Account<'info, Offer>,
@@ -38,10 +62,19 @@ pub struct CreateOffer<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
     #[account(
     mut,
     associated_token::mint = token_mint_in,
-    associated_token::authority = signer,
+    associated_token::authority = depositor,
     associated_token::token_program = token_program,
 )]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>,
@@ -51,28 +84,80 @@ pub struct CreateOffer<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn process_create_offer(ctx: Context<CreateOffer>, amount: u64) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+pub fn process_create_offer(
+    ctx: Context<CreateOffer>,
+    amount: u64,
+    auction: Option<AuctionParams>,
+    trigger: Option<TriggerParams>,
+    dca: Option<DcaParams>,
+    client_order_id: u128,
+    memo: Option<String>,
+    barter_amount_out: Option<u64>,
+) -> Result<()> {
     msg!("amount: {}", amount);
     if amount == 0 {
         return Err(ErrorCode::AmountZero.into());
     }
 
+    // The offer PDA is per depositor and mint, so a live offer must be cancelled first
+    require!(
+        ctx.accounts.offer.depositor == Pubkey::default(),
+        ErrorCode::OfferAlreadyExists
+    );
+
+    let memo = memo.unwrap_or_default();
+    require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+
+    check_offer_authority(
+        &ctx.accounts.signer.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.accounts.delegate.as_deref(),
+        DELEGATE_CREATE,
+    )?;
+    if ctx.accounts.signer.key() != ctx.accounts.depositor.key() {
+        // Managers may only create offers that pay the depositor
+        require!(
+            ctx.accounts.proceeds_recipient.is_none(),
+            ErrorCode::Unauthorized
+        );
+        // ...and price them at the oracle, so they cannot list the depositor's
+        // tokens cheaply and take them
+        require!(
+            barter_amount_out.is_none()
+                && auction.is_none()
+                && !dca.is_some_and(|dca| dca.limit_price.is_some() || dca.spread_bps > 0),
+            ErrorCode::ManagerPricing
+        );
+    }
+
     let token_in = ctx.accounts.token_mint_in.key();
     let token_out = ctx.accounts.token_mint_out.key();
     let usdc_mint = ctx.accounts.config.usdc_mint;
-    let wsol_mint = pubkey!("So11111111111111111111111111111111111111112");
 
-    // Only USDC and WSOL are allowed
-    let valid_mints = [usdc_mint, wsol_mint];
+    match barter_amount_out {
+        // Barter offers name their exact price, so any mint can be swapped
+        Some(amount_out) => {
+            require!(amount_out > 0, ErrorCode::AmountZero);
+            require!(
+                auction.is_none() && trigger.is_none() && dca.is_none(),
+                ErrorCode::InvalidBarter
+            );
+        }
+        None => {
+            // Only USDC and WSOL are allowed
+            let valid_mints = [usdc_mint, WSOL_MINT];
 
-    // Check that mint_in is allowed
-    if !valid_mints.contains(&token_in) {
-        return Err(ErrorCode::InvalidTokenIn.into());
-    }
+            // Check that mint_in is allowed
+            if !valid_mints.contains(&token_in) {
+                return Err(ErrorCode::InvalidTokenIn.into());
+            }
 
-    // Check that mint_out is allowed
-    if !valid_mints.contains(&token_out) {
-        return Err(ErrorCode::InvalidTokenOut.into());
+            // Check that mint_out is allowed
+            if !valid_mints.contains(&token_out) {
+                return Err(ErrorCode::InvalidTokenOut.into());
+            }
+        }
     }
 
     // Prevent them from being the same (meaningless swap)
@@ -80,27 +165,141 @@ pub fn process_create_offer(ctx: Context<CreateOffer>, amount: u64) -> Result<()
         return Err(ErrorCode::SameToken.into());
     }
 
-    let cpi_acounts = TransferChecked {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.vault.to_account_info(),
-        authority: ctx.accounts.signer.to_account_info(),
-        mint: ctx.accounts.token_mint_in.to_account_info(),
+    if let Some(limits) = MintLimits::load(&ctx.accounts.mint_limits)? {
+        limits.check_deposit(amount)?;
+    }
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    let auction = match auction {
+        Some(params) => {
+            params.validate(token_in != WSOL_MINT)?;
+            Some(Auction {
+                pricing: params.pricing,
+                start_time: now,
+                duration: params.duration,
+            })
+        }
+        None => None,
+    };
+
+    // The first tranche is available immediately
+    let dca = match dca {
+        Some(params) => {
+            params.validate()?;
+            Some(Dca {
+                tranche_size: params.tranche_size,
+                interval: params.interval,
+                next_available_at: now,
+                limit_price: params.limit_price,
+                spread_bps: params.spread_bps,
+            })
+        }
+        None => None,
+    };
+
+    // Escrow the keeper bounty in the offer account itself
+    let trigger = match trigger {
+        Some(params) => {
+            require!(params.price > 0, ErrorCode::InvalidPrice);
+            if params.bounty > 0 {
+                let cpi_ctx = CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.signer.to_account_info(),
+                        to: ctx.accounts.offer.to_account_info(),
+                    },
+                );
+                transfer(cpi_ctx, params.bounty)?;
+            }
+            Some(Trigger {
+                price: params.price,
+                direction: params.direction,
+                bounty: params.bounty,
+                armed: false,
+            })
+        }
+        None => None,
     };
 
     let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_acounts);
 
-    transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint_in.decimals)?;
+    match &ctx.accounts.delegate {
+        // Managers move the deposit through the approval the depositor granted the delegate PDA
+        Some(delegate) if ctx.accounts.signer.key() != ctx.accounts.depositor.key() => {
+            let cpi_acounts = TransferChecked {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: delegate.to_account_info(),
+                mint: ctx.accounts.token_mint_in.to_account_info(),
+            };
+
+            let depositor = ctx.accounts.depositor.key();
+            let signer = ctx.accounts.signer.key();
+            let delegate_seeds = &[
+                b"delegate".as_ref(),
+                depositor.as_ref(),
+                signer.as_ref(),
+                &[delegate.bump],
+            ];
+            let signer_seeds = &[&delegate_seeds[..]];
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_acounts, signer_seeds);
+
+            transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint_in.decimals)?;
+        }
+        _ => {
+            let cpi_acounts = TransferChecked {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info(),
+                mint: ctx.accounts.token_mint_in.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_acounts);
+
+            transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint_in.decimals)?;
+        }
+    }
 
     *ctx.accounts.offer = Offer {
+        version: OFFER_VERSION,
         token_mint_in: ctx.accounts.token_mint_in.key(),
         token_mint_out: ctx.accounts.token_mint_out.key(),
-        depositor: ctx.accounts.signer.key(),
+        depositor: ctx.accounts.depositor.key(),
         vault: ctx.accounts.vault.key(),
         deposited_amount: amount,
         bump: ctx.bumps.offer,
+        client_order_id,
+        auction,
+        trigger,
+        dca,
+        memo: memo.clone(),
+        barter_amount_out,
+        vesting: None,
+        position_mint: None,
+        proceeds_recipient: ctx.accounts.proceeds_recipient.as_ref().map(|r| r.key()),
+        grid: None,
+        taker_merkle_root: None,
+        taker_gate: None,
+        updated_slot: clock.slot,
+        reserved: [0; OFFER_RESERVED_SPACE],
     };
 
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(token_in, token_out, ctx.bumps.stats);
+    stats.record_created(amount);
+
+    emit!(OfferCreated {
+        offer: ctx.accounts.offer.key(),
+        depositor: ctx.accounts.depositor.key(),
+        token_mint_in: token_in,
+        token_mint_out: token_out,
+        amount,
+        client_order_id,
+        memo,
+    });
+
     msg!("Offer created: {}", ctx.accounts.offer.key());
 
     Ok(())
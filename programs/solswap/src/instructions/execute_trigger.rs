@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+
+use crate::{error::ErrorCode, Offer, TriggerArmed, FEED_ID, MAXIMUM_AGE};
+
+#[derive(Accounts)]
+pub struct ExecuteTrigger<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [offer.token_mint_in.as_ref(), offer.depositor.as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+}
+
+/// Arms a trigger offer once the oracle has crossed its threshold and pays
+/// the escrowed bounty to the keeper.
+pub fn process_execute_trigger(ctx: Context<ExecuteTrigger>) -> Result<()> {
+    let price_info = ctx.accounts.price_update.get_price_no_older_than(
+        &Clock::get()?,
+        MAXIMUM_AGE,
+        &get_feed_id_from_hex(FEED_ID)?,
+    )?;
+
+    let price = price_info.price;
+    msg!("Current price: {}", price);
+
+    let offer = &mut ctx.accounts.offer;
+    let trigger = offer.trigger.as_mut().ok_or(ErrorCode::NotATriggerOffer)?;
+    require!(!trigger.armed, ErrorCode::TriggerAlreadyArmed);
+    require!(trigger.is_met(price), ErrorCode::TriggerNotMet);
+
+    trigger.armed = true;
+    let bounty = trigger.bounty;
+
+    if bounty > 0 {
+        offer.sub_lamports(bounty)?;
+        ctx.accounts.keeper.add_lamports(bounty)?;
+    }
+
+    emit!(TriggerArmed {
+        offer: offer.key(),
+        keeper: ctx.accounts.keeper.key(),
+        price,
+        bounty,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::error::ErrorCode;
+
+/// Sends the protocol's share of a taker fee, what is left of it after the
+/// maker rebate, from the taker's `from` account to the admin's fee account.
+pub fn pay_protocol_fee<'info>(
+    protocol_fee: u64,
+    from: AccountInfo<'info>,
+    taker: AccountInfo<'info>,
+    fee_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    if protocol_fee == 0 {
+        return Ok(());
+    }
+    let fee_token_account = fee_token_account.ok_or(ErrorCode::MissingFeeAccount)?;
+    let cpi_accounts = TransferChecked {
+        from,
+        to: fee_token_account.to_account_info(),
+        authority: taker,
+        mint: mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_ctx, protocol_fee, mint.decimals)
+}
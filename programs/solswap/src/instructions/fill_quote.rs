@@ -0,0 +1,311 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::ErrorCode, QuoteNonces, SignedQuote};
+
+#[derive(Accounts)]
+#[instruction(quote: SignedQuote)]
+pub struct FillQuote<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub maker: SystemAccount<'info>,
+
+    pub mint_in: InterfaceAccount<'info, Mint>,
+    pub mint_out: InterfaceAccount<'info, Mint>,
+
+    /// Maker's source account; must have approved `quote_authority` as delegate
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_token_in_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_out,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_receive_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_in,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_in_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_out,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_out_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA makers approve as token delegate; it holds no data
+    #[account(seeds = [b"quote_authority"], bump)]
+    pub quote_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + QuoteNonces::INIT_SPACE,
+        seeds = [b"quote_nonces", maker.key().as_ref(), &quote.nonce_page().to_le_bytes()],
+        bump
+    )]
+    pub quote_nonces: Account<'info, QuoteNonces>,
+
+    /// CHECK: address is checked against the instructions sysvar id
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn process_fill_quote(ctx: Context<FillQuote>, quote: SignedQuote) -> Result<()> {
+    require!(
+        quote.amount_in > 0 && quote.amount_out > 0,
+        ErrorCode::AmountZero
+    );
+    require!(
+        Clock::get()?.unix_timestamp <= quote.expiry,
+        ErrorCode::QuoteExpired
+    );
+    require_keys_neq!(quote.mint_in, quote.mint_out, ErrorCode::SameToken);
+    require_keys_eq!(
+        quote.maker,
+        ctx.accounts.maker.key(),
+        ErrorCode::QuoteMismatch
+    );
+    require_keys_eq!(
+        quote.taker,
+        ctx.accounts.taker.key(),
+        ErrorCode::QuoteMismatch
+    );
+    require_keys_eq!(
+        quote.mint_in,
+        ctx.accounts.mint_in.key(),
+        ErrorCode::QuoteMismatch
+    );
+    require_keys_eq!(
+        quote.mint_out,
+        ctx.accounts.mint_out.key(),
+        ErrorCode::QuoteMismatch
+    );
+
+    verify_quote_signature(
+        &ctx.accounts.instructions.to_account_info(),
+        &quote.maker,
+        &quote.message()?,
+    )?;
+
+    // Record the nonce before moving any funds so a quote can only settle once
+    let quote_nonces = &mut ctx.accounts.quote_nonces;
+    quote_nonces.maker = quote.maker;
+    quote_nonces.page = quote.nonce_page();
+    quote_nonces.bump = ctx.bumps.quote_nonces;
+    quote_nonces.consume(quote.nonce)?;
+
+    // Taker pays the maker
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.taker_token_out_ata.to_account_info(),
+        to: ctx.accounts.maker_receive_ata.to_account_info(),
+        authority: ctx.accounts.taker.to_account_info(),
+        mint: ctx.accounts.mint_out.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_ctx, quote.amount_out, ctx.accounts.mint_out.decimals)?;
+
+    // Maker's tokens move through the delegate approval granted to the quote authority
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.maker_token_in_ata.to_account_info(),
+        to: ctx.accounts.taker_token_in_ata.to_account_info(),
+        authority: ctx.accounts.quote_authority.to_account_info(),
+        mint: ctx.accounts.mint_in.to_account_info(),
+    };
+    let authority_seeds: &[&[u8]] = &[b"quote_authority", &[ctx.bumps.quote_authority]];
+    let signer_seeds = &[authority_seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    transfer_checked(cpi_ctx, quote.amount_in, ctx.accounts.mint_in.decimals)?;
+
+    msg!("Quote {} filled for maker {}", quote.nonce, quote.maker);
+
+    Ok(())
+}
+
+/// Checks that the instruction right before this one is an ed25519 precompile
+/// verification of `message` signed by `signer`.
+fn verify_quote_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)?;
+    require!(current_index > 0, ErrorCode::InvalidQuoteSignature);
+
+    let ed25519_index = current_index - 1;
+    let ix = load_instruction_at_checked(ed25519_index as usize, instructions)?;
+    require_keys_eq!(
+        ix.program_id,
+        ed25519_program::ID,
+        ErrorCode::InvalidQuoteSignature
+    );
+
+    check_ed25519_data(&ix.data, ed25519_index, signer, message)
+}
+
+/// Checks the data of the ed25519 precompile instruction at `ed25519_index`
+/// verifies a single signature by `signer` over `message`.
+fn check_ed25519_data(
+    data: &[u8],
+    ed25519_index: u16,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    // Header: signature count and padding, then a single 14 byte offsets struct
+    require!(
+        data.len() >= 16 && data[0] == 1,
+        ErrorCode::InvalidQuoteSignature
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+
+    // Every offset must point into the precompile instruction's own data
+    let own_data = |index: usize| index == u16::MAX as usize || index == ed25519_index as usize;
+    require!(
+        own_data(read_u16(4)) && own_data(read_u16(8)) && own_data(read_u16(14)),
+        ErrorCode::InvalidQuoteSignature
+    );
+
+    let public_key_offset = read_u16(6);
+    let message_offset = read_u16(10);
+    let message_size = read_u16(12);
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(ErrorCode::InvalidQuoteSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidQuoteSignature)?;
+
+    require!(
+        public_key == signer.as_ref() && signed_message == message,
+        ErrorCode::InvalidQuoteSignature
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ED25519_INDEX: u16 = 1;
+    const MESSAGE: &[u8] = b"quote";
+
+    /// Precompile data in the layout the ed25519 program's clients produce:
+    /// header, public key, signature, then the message.
+    fn ed25519_data(signer: &Pubkey, instruction_index: u16) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            instruction_index,
+            public_key_offset,
+            instruction_index,
+            message_offset,
+            MESSAGE.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(MESSAGE);
+        data
+    }
+
+    #[test]
+    fn accepts_offsets_into_its_own_data() {
+        let signer = Pubkey::new_unique();
+
+        for index in [u16::MAX, ED25519_INDEX] {
+            let data = ed25519_data(&signer, index);
+            assert!(check_ed25519_data(&data, ED25519_INDEX, &signer, MESSAGE).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_offsets_into_another_instruction() {
+        let signer = Pubkey::new_unique();
+        let data = ed25519_data(&signer, ED25519_INDEX + 1);
+
+        assert_eq!(
+            check_ed25519_data(&data, ED25519_INDEX, &signer, MESSAGE).unwrap_err(),
+            ErrorCode::InvalidQuoteSignature.into()
+        );
+    }
+
+    #[test]
+    fn rejects_another_signer_or_message() {
+        let signer = Pubkey::new_unique();
+        let data = ed25519_data(&signer, u16::MAX);
+
+        assert_eq!(
+            check_ed25519_data(&data, ED25519_INDEX, &Pubkey::new_unique(), MESSAGE).unwrap_err(),
+            ErrorCode::InvalidQuoteSignature.into()
+        );
+        assert_eq!(
+            check_ed25519_data(&data, ED25519_INDEX, &signer, b"other").unwrap_err(),
+            ErrorCode::InvalidQuoteSignature.into()
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let signer = Pubkey::new_unique();
+        let data = ed25519_data(&signer, u16::MAX);
+
+        // Cut inside the header, the public key and the message
+        for len in [0, 15, 40, data.len() - 1] {
+            assert_eq!(
+                check_ed25519_data(&data[..len], ED25519_INDEX, &signer, MESSAGE).unwrap_err(),
+                ErrorCode::InvalidQuoteSignature.into()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_more_than_one_signature() {
+        let signer = Pubkey::new_unique();
+        let mut data = ed25519_data(&signer, u16::MAX);
+        data[0] = 2;
+
+        assert_eq!(
+            check_ed25519_data(&data, ED25519_INDEX, &signer, MESSAGE).unwrap_err(),
+            ErrorCode::InvalidQuoteSignature.into()
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+
+use crate::{
+    error::ErrorCode, Config, Offer, OfferTaken, PairBreaker, Stats, TraderAccount, FEED_ID,
+    MAXIMUM_AGE,
+};
+
+#[derive(Accounts)]
+pub struct FlashTakeOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_in_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: the offer's proceeds wallet; checked in the handler
+    pub proceeds_owner: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = proceeds_owner,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_receive_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = depositor,
+        has_one = token_mint_in,
+        has_one = token_mint_out,
+        seeds = [token_mint_in.key().as_ref(), depositor.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + PairBreaker::INIT_SPACE,
+        seeds = [
+            b"breaker",
+            token_mint_in.key().min(token_mint_out.key()).as_ref(),
+            token_mint_in.key().max(token_mint_out.key()).as_ref(),
+        ],
+        bump
+    )]
+    pub breaker: Box<Account<'info, PairBreaker>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", taker.key().as_ref()],
+        bump
+    )]
+    pub taker_trader: Box<Account<'info, TraderAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", depositor.key().as_ref()],
+        bump
+    )]
+    pub depositor_trader: Box<Account<'info, TraderAccount>>,
+
+    /// Admin token account the callback pays the protocol share of the taker fee to
+    #[account(
+        mut,
+        token::mint = token_mint_out,
+        token::token_program = token_program,
+        constraint = fee_token_account.owner == config.admin @ ErrorCode::Unauthorized,
+    )]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    /// CHECK: arbitrary taker program invoked with `remaining_accounts`; must be executable
+    #[account(
+        executable,
+        constraint = callback_program.key() != crate::ID @ ErrorCode::InvalidCallbackProgram
+    )]
+    pub callback_program: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Sends the vault to the taker up front, lets the taker's callback program
+/// source the payment, then checks the depositor was paid the oracle price
+/// plus their maker rebate, and the fee account the rest of the taker fee.
+pub fn process_flash_take_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FlashTakeOffer<'info>>,
+    callback_data: Vec<u8>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let price_info = ctx.accounts.price_update.get_price_no_older_than(
+        &clock,
+        MAXIMUM_AGE,
+        &get_feed_id_from_hex(FEED_ID)?,
+    )?;
+
+    let price = price_info.price;
+    msg!("Current price: {}", price);
+
+    let offer = &ctx.accounts.offer;
+    let now = clock.unix_timestamp;
+    require!(offer.is_fillable(price, now), ErrorCode::OfferNotFillable);
+    // The taker must hold the tokens to fund the payment, so they cannot vest
+    require!(offer.vesting.is_none(), ErrorCode::VestingNotSupported);
+    require!(offer.position_mint.is_none(), ErrorCode::OfferTokenized);
+    require!(offer.grid.is_none(), ErrorCode::GridNotSupported);
+    require!(
+        offer.taker_merkle_root.is_none(),
+        ErrorCode::AllowlistNotSupported
+    );
+    require!(offer.taker_gate.is_none(), ErrorCode::TokenGateNotSupported);
+    offer.check_proceeds_owner(ctx.accounts.proceeds_owner.key, None)?;
+
+    ctx.accounts
+        .taker_trader
+        .init_if_empty(ctx.accounts.taker.key(), ctx.bumps.taker_trader);
+    ctx.accounts
+        .depositor_trader
+        .init_if_empty(offer.depositor, ctx.bumps.depositor_trader);
+    let fees = ctx.accounts.config.fee_rates(
+        ctx.accounts.taker_trader.rolling_volume(now),
+        ctx.accounts.depositor_trader.rolling_volume(now),
+    );
+
+    let amount_in = offer.available_amount(now);
+    let quote = offer.quote(amount_in, price, now, 0, fees)?;
+    let amount_owed = quote.amount_out + quote.maker_rebate;
+    let protocol_fee = quote.taker_fee - quote.maker_rebate;
+    let balance_before = ctx.accounts.depositor_receive_ata.amount;
+    let fee_balance_before = match &ctx.accounts.fee_token_account {
+        Some(fee_token_account) => fee_token_account.amount,
+        None if protocol_fee > 0 => return err!(ErrorCode::MissingFeeAccount),
+        None => 0,
+    };
+
+    let notional = offer.usdc_notional(amount_in, quote.amount_out);
+    let breaker = &mut ctx.accounts.breaker;
+    breaker.init_if_empty(offer.token_mint_in, offer.token_mint_out, ctx.bumps.breaker);
+    breaker.guard_fill(&ctx.accounts.config.circuit_breaker, price, notional, now)?;
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(offer.token_mint_in, offer.token_mint_out, ctx.bumps.stats);
+
+    // Transfer the offered tokens from the vault to the taker before they pay
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.taker_token_in_ata.to_account_info(),
+        authority: offer.to_account_info(),
+        mint: ctx.accounts.token_mint_in.to_account_info(),
+    };
+
+    let token_mint_in = ctx.accounts.token_mint_in.key();
+    let depositor = ctx.accounts.depositor.key();
+    let offer_seeds = &[token_mint_in.as_ref(), depositor.as_ref(), &[offer.bump]];
+    let signer_seeds = &[&offer_seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+
+    transfer_checked(cpi_ctx, amount_in, ctx.accounts.token_mint_in.decimals)?;
+
+    // Hand control to the taker's program; signer privileges are passed through as-is
+    let callback = Instruction {
+        program_id: ctx.accounts.callback_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: callback_data,
+    };
+    invoke(&callback, ctx.remaining_accounts)?;
+
+    // The callback must have paid the depositor at least the oracle price
+    ctx.accounts.depositor_receive_ata.reload()?;
+    let amount_paid = ctx
+        .accounts
+        .depositor_receive_ata
+        .amount
+        .saturating_sub(balance_before);
+    msg!("Depositor paid {} of {} owed", amount_paid, amount_owed);
+    require!(amount_paid >= amount_owed, ErrorCode::FlashTakeUnderpaid);
+    if let Some(fee_token_account) = ctx.accounts.fee_token_account.as_mut() {
+        fee_token_account.reload()?;
+        require!(
+            fee_token_account.amount.saturating_sub(fee_balance_before) >= protocol_fee,
+            ErrorCode::FlashTakeUnderpaid
+        );
+    }
+
+    emit!(OfferTaken {
+        offer: ctx.accounts.offer.key(),
+        depositor,
+        taker: ctx.accounts.taker.key(),
+        token_mint_in,
+        token_mint_out: ctx.accounts.token_mint_out.key(),
+        amount_in,
+        amount_out: amount_paid,
+        referrer: None,
+        referral_amount: 0,
+        client_order_id: ctx.accounts.offer.client_order_id,
+    });
+
+    let stats = &mut ctx.accounts.stats;
+    stats.record_fill(amount_in, amount_paid, price, now);
+    ctx.accounts.taker_trader.record_volume(notional, now);
+    ctx.accounts.depositor_trader.record_volume(notional, now);
+
+    let offer = &mut ctx.accounts.offer;
+    offer.record_fill(amount_in, now);
+    if offer.deposited_amount == 0 {
+        offer.close(ctx.accounts.taker.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,340 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{
+    check_offer_authority, error::ErrorCode, Delegate, Grid, Offer, OfferCancelled, Stats,
+    DELEGATE_AMEND, DELEGATE_CANCEL,
+};
+
+#[derive(Accounts)]
+pub struct SetOfferGrid<'info> {
+    /// Depositor, or a manager holding a delegation from them; pays the
+    /// reverse offer's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"delegate", depositor.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    #[account(
+        mut,
+        has_one = depositor,
+        has_one = token_mint_out,
+        seeds = [offer.token_mint_in.as_ref(), depositor.key().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    /// Depositor's offer on the other side of the pair, which fills are
+    /// reposted into; required when setting a grid
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Offer::INIT_SPACE,
+        seeds = [token_mint_out.key().as_ref(), depositor.key().as_ref()],
+        bump
+    )]
+    pub reverse_offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = reverse_offer,
+        associated_token::token_program = token_program,
+    )]
+    pub reverse_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Turns a plain oracle offer into a grid offer filling at `grid.price`, or
+/// back into a plain offer. Setting a grid creates the empty reverse offer
+/// fills are reposted into, so its rent is paid by the depositor's side
+/// rather than by takers.
+pub fn process_set_offer_grid(ctx: Context<SetOfferGrid>, grid: Option<Grid>) -> Result<()> {
+    check_offer_authority(
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.accounts.delegate.as_deref(),
+        DELEGATE_AMEND,
+    )?;
+
+    if let Some(grid) = grid {
+        // Grids fill at a fixed price, which managers could set to take the deposit cheaply
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            ctx.accounts.depositor.key(),
+            ErrorCode::ManagerPricing
+        );
+        grid.validate()?;
+        // Proceeds are reposted for the depositor, so they cannot belong to anyone else
+        let offer = &ctx.accounts.offer;
+        require!(
+            offer.auction.is_none()
+                && offer.trigger.is_none()
+                && offer.dca.is_none()
+                && offer.barter_amount_out.is_none()
+                && offer.position_mint.is_none()
+                && offer.proceeds_recipient.is_none(),
+            ErrorCode::InvalidGrid
+        );
+    }
+
+    let slot = Clock::get()?.slot;
+    ctx.accounts.offer.grid = grid;
+    ctx.accounts.offer.updated_slot = slot;
+
+    if grid.is_some() {
+        let (Some(reverse_offer), Some(reverse_vault)) = (
+            ctx.accounts.reverse_offer.as_mut(),
+            &ctx.accounts.reverse_vault,
+        ) else {
+            return err!(ErrorCode::MissingGridAccounts);
+        };
+        if reverse_offer.depositor == Pubkey::default() {
+            let bump = ctx
+                .bumps
+                .reverse_offer
+                .ok_or(ErrorCode::MissingGridAccounts)?;
+            reverse_offer.set_inner(ctx.accounts.offer.grid_reverse(
+                reverse_vault.key(),
+                bump,
+                slot,
+            )?);
+        }
+        require!(reverse_offer.grid.is_some(), ErrorCode::GridSlotTaken);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelGrid<'info> {
+    /// Depositor, or a manager holding a delegation from them
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"delegate", depositor.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor,
+        seeds = [token_mint_in.key().as_ref(), depositor.key().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor,
+        seeds = [token_mint_out.key().as_ref(), depositor.key().as_ref()],
+        bump = reverse_offer.bump
+    )]
+    pub reverse_offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = reverse_offer,
+        associated_token::token_program = token_program
+    )]
+    pub reverse_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_in: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_out: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_out.key().as_ref(), token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub reverse_stats: Box<Account<'info, Stats>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Cancels both sides of a grid in one go, returning each vault to the
+/// depositor. Either side may already be closed.
+pub fn process_cancel_grid(ctx: Context<CancelGrid>) -> Result<()> {
+    check_offer_authority(
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.accounts.delegate.as_deref(),
+        DELEGATE_CANCEL,
+    )?;
+
+    let accounts = ctx.accounts;
+    require!(
+        accounts.offer.is_some() || accounts.reverse_offer.is_some(),
+        ErrorCode::MissingGridAccounts
+    );
+
+    if let Some(offer) = &accounts.offer {
+        let vault = accounts
+            .vault
+            .as_ref()
+            .ok_or(ErrorCode::MissingGridAccounts)?;
+        let stats = &mut accounts.stats;
+        stats.init_if_empty(offer.token_mint_in, offer.token_mint_out, ctx.bumps.stats);
+        cancel_side(
+            offer,
+            vault,
+            &accounts.token_mint_in,
+            &accounts.depositor_token_in,
+            stats,
+            &accounts.depositor,
+            &accounts.authority,
+            &accounts.token_program,
+        )?;
+    }
+
+    if let Some(offer) = &accounts.reverse_offer {
+        let vault = accounts
+            .reverse_vault
+            .as_ref()
+            .ok_or(ErrorCode::MissingGridAccounts)?;
+        let stats = &mut accounts.reverse_stats;
+        stats.init_if_empty(
+            offer.token_mint_in,
+            offer.token_mint_out,
+            ctx.bumps.reverse_stats,
+        );
+        cancel_side(
+            offer,
+            vault,
+            &accounts.token_mint_out,
+            &accounts.depositor_token_out,
+            stats,
+            &accounts.depositor,
+            &accounts.authority,
+            &accounts.token_program,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Refunds one side of the grid and closes its vault; the offer itself is
+/// closed by its `close` constraint.
+#[allow(clippy::too_many_arguments)]
+fn cancel_side<'info>(
+    offer: &Account<'info, Offer>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    depositor_token_account: &InterfaceAccount<'info, TokenAccount>,
+    stats: &mut Stats,
+    depositor: &SystemAccount<'info>,
+    authority: &Signer<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    require!(offer.grid.is_some(), ErrorCode::InvalidGrid);
+
+    let token_mint_in = offer.token_mint_in;
+    let depositor_key = depositor.key();
+    let offer_seeds = &[
+        token_mint_in.as_ref(),
+        depositor_key.as_ref(),
+        &[offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    let amount = vault.amount;
+    if amount > 0 {
+        let cpi_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            to: depositor_token_account.to_account_info(),
+            authority: offer.to_account_info(),
+            mint: mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    }
+
+    let cpi_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: depositor.to_account_info(),
+        authority: offer.to_account_info(),
+    };
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    close_account(cpi_ctx)?;
+
+    stats.record_cancelled(offer.deposited_amount);
+
+    emit!(OfferCancelled {
+        offer: offer.key(),
+        depositor: depositor_key,
+        authority: authority.key(),
+        amount_returned: amount,
+        client_order_id: offer.client_order_id,
+    });
+
+    Ok(())
+}
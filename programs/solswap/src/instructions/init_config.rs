@@ -1,4 +1,7 @@
-use crate::Config;
+use crate::{
+    error::ErrorCode, validate_fee_tiers, CircuitBreaker, Config, FeeTier, BPS_DENOMINATOR,
+    CONFIG_VERSION, MAX_FEE_TIERS,
+};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -18,6 +21,61 @@ pub struct InitConfig<'info> {
 }
 
 pub fn process_init_config(ctx: Context<InitConfig>, usdc_mint: Pubkey) -> Result<()> {
-    ctx.accounts.config.usdc_mint = usdc_mint;
+    let config = &mut ctx.accounts.config;
+
+    // The first initializer becomes the admin; only they can re-run it afterwards
+    if config.admin == Pubkey::default() {
+        config.admin = ctx.accounts.payer.key();
+    }
+    require_keys_eq!(
+        config.admin,
+        ctx.accounts.payer.key(),
+        ErrorCode::Unauthorized
+    );
+
+    config.version = CONFIG_VERSION;
+    config.usdc_mint = usdc_mint;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config"], bump
+        )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn process_update_config(ctx: Context<UpdateConfig>, max_referral_bps: u16) -> Result<()> {
+    require!(
+        (max_referral_bps as u64) < BPS_DENOMINATOR,
+        ErrorCode::InvalidReferralBps
+    );
+
+    ctx.accounts.config.max_referral_bps = max_referral_bps;
+    Ok(())
+}
+
+pub fn process_set_circuit_breaker(
+    ctx: Context<UpdateConfig>,
+    circuit_breaker: CircuitBreaker,
+) -> Result<()> {
+    circuit_breaker.validate()?;
+
+    ctx.accounts.config.circuit_breaker = circuit_breaker;
+    Ok(())
+}
+
+pub fn process_set_fee_tiers(ctx: Context<UpdateConfig>, fee_tiers: Vec<FeeTier>) -> Result<()> {
+    validate_fee_tiers(&fee_tiers)?;
+
+    let config = &mut ctx.accounts.config;
+    config.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    config.fee_tiers[..fee_tiers.len()].copy_from_slice(&fee_tiers);
+    config.fee_tier_count = fee_tiers.len() as u8;
     Ok(())
 }
@@ -0,0 +1,457 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use super::pay_protocol_fee;
+use crate::{
+    error::ErrorCode, Config, Ladder, LadderLevelTaken, LadderParams, MintLimits, PairBreaker,
+    Stats, TraderAccount, WSOL_MINT,
+};
+
+#[derive(Accounts)]
+#[instruction(ladder_id: u64)]
+pub struct CreateLadder<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: limits PDA for `token_mint_in`; may be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub mint_limits: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + Ladder::INIT_SPACE,
+        seeds = [b"ladder", depositor.key().as_ref(), ladder_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub ladder: Box<Account<'info, Ladder>>,
+
+    #[account(
+        init,
+        payer = depositor,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = ladder,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Escrows `amount` and splits it across the price levels described by `params`.
+pub fn process_create_ladder(
+    ctx: Context<CreateLadder>,
+    ladder_id: u64,
+    amount: u64,
+    params: LadderParams,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::AmountZero);
+
+    let token_in = ctx.accounts.token_mint_in.key();
+    let token_out = ctx.accounts.token_mint_out.key();
+    let valid_mints = [ctx.accounts.config.usdc_mint, WSOL_MINT];
+    require!(valid_mints.contains(&token_in), ErrorCode::InvalidTokenIn);
+    require!(valid_mints.contains(&token_out), ErrorCode::InvalidTokenOut);
+    require!(token_in != token_out, ErrorCode::SameToken);
+
+    let levels = params.levels(amount, token_in == WSOL_MINT)?;
+    if let Some(limits) = MintLimits::load(&ctx.accounts.mint_limits)? {
+        for level in &levels[..params.levels as usize] {
+            limits.check_deposit(level.amount)?;
+        }
+    }
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.depositor_token_account.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.depositor.to_account_info(),
+        mint: ctx.accounts.token_mint_in.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint_in.decimals)?;
+
+    **ctx.accounts.ladder = Ladder {
+        depositor: ctx.accounts.depositor.key(),
+        ladder_id,
+        token_mint_in: token_in,
+        token_mint_out: token_out,
+        vault: ctx.accounts.vault.key(),
+        levels,
+        level_count: params.levels,
+        bump: ctx.bumps.ladder,
+    };
+
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(token_in, token_out, ctx.bumps.stats);
+    stats.record_created(amount);
+
+    msg!("Ladder created: {}", ctx.accounts.ladder.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TakeLadderLevel<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: limits PDA for `token_mint_in`; may be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub mint_limits: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = depositor,
+        has_one = token_mint_in,
+        has_one = token_mint_out,
+        has_one = vault,
+        seeds = [b"ladder", depositor.key().as_ref(), ladder.ladder_id.to_le_bytes().as_ref()],
+        bump = ladder.bump
+    )]
+    pub ladder: Box<Account<'info, Ladder>>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_in_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_out_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_receive_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + PairBreaker::INIT_SPACE,
+        seeds = [
+            b"breaker",
+            token_mint_in.key().min(token_mint_out.key()).as_ref(),
+            token_mint_in.key().max(token_mint_out.key()).as_ref(),
+        ],
+        bump
+    )]
+    pub breaker: Box<Account<'info, PairBreaker>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", taker.key().as_ref()],
+        bump
+    )]
+    pub taker_trader: Box<Account<'info, TraderAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", depositor.key().as_ref()],
+        bump
+    )]
+    pub depositor_trader: Box<Account<'info, TraderAccount>>,
+
+    /// Admin token account receiving the protocol share of the taker fee
+    #[account(
+        mut,
+        token::mint = token_mint_out,
+        token::token_program = token_program,
+        constraint = fee_token_account.owner == config.admin @ ErrorCode::Unauthorized,
+    )]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Takes `amount` from one level at its fixed price. A partial take must not
+/// leave the level below the mint's minimum offer size. The fill counts
+/// towards the pair's circuit breaker notional, but no oracle is read. The
+/// taker pays their fee tier on top of the level's price, as in `take_offer`.
+/// Empty ladders stay open until the depositor cancels them.
+pub fn process_take_ladder_level(
+    ctx: Context<TakeLadderLevel>,
+    level: u8,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::AmountZero);
+
+    let ladder = &ctx.accounts.ladder;
+    let entry = *ladder
+        .levels()
+        .get(level as usize)
+        .ok_or(ErrorCode::InvalidLadder)?;
+    require!(amount <= entry.amount, ErrorCode::AmountTooLarge);
+    if let Some(limits) = MintLimits::load(&ctx.accounts.mint_limits)? {
+        require!(
+            limits.max_fill(entry.amount, amount) == amount,
+            ErrorCode::AmountTooSmall
+        );
+    }
+
+    let amount_out = ladder.amount_out_for(amount, entry.price);
+    require!(amount_out > 0, ErrorCode::AmountZero);
+
+    let now = Clock::get()?.unix_timestamp;
+    let notional = ladder.usdc_notional(amount, amount_out);
+    let breaker = &mut ctx.accounts.breaker;
+    breaker.init_if_empty(
+        ladder.token_mint_in,
+        ladder.token_mint_out,
+        ctx.bumps.breaker,
+    );
+    breaker.guard_notional(&ctx.accounts.config.circuit_breaker, notional, now)?;
+
+    ctx.accounts
+        .taker_trader
+        .init_if_empty(ctx.accounts.taker.key(), ctx.bumps.taker_trader);
+    ctx.accounts
+        .depositor_trader
+        .init_if_empty(ctx.accounts.depositor.key(), ctx.bumps.depositor_trader);
+    let fees = ctx.accounts.config.fee_rates(
+        ctx.accounts.taker_trader.rolling_volume(now),
+        ctx.accounts.depositor_trader.rolling_volume(now),
+    );
+    let (taker_fee, maker_rebate) = fees.fees_on(amount_out);
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.taker_token_out_ata.to_account_info(),
+        to: ctx.accounts.depositor_receive_ata.to_account_info(),
+        authority: ctx.accounts.taker.to_account_info(),
+        mint: ctx.accounts.token_mint_out.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    // The maker rebate is carved from the taker fee and paid with the price
+    transfer_checked(
+        cpi_ctx,
+        amount_out + maker_rebate,
+        ctx.accounts.token_mint_out.decimals,
+    )?;
+    pay_protocol_fee(
+        taker_fee - maker_rebate,
+        ctx.accounts.taker_token_out_ata.to_account_info(),
+        ctx.accounts.taker.to_account_info(),
+        ctx.accounts.fee_token_account.as_ref(),
+        &ctx.accounts.token_mint_out,
+        &ctx.accounts.token_program,
+    )?;
+
+    let depositor = ladder.depositor;
+    let ladder_id = ladder.ladder_id.to_le_bytes();
+    let ladder_seeds = &[
+        b"ladder".as_ref(),
+        depositor.as_ref(),
+        ladder_id.as_ref(),
+        &[ladder.bump],
+    ];
+    let signer_seeds = &[&ladder_seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.taker_token_in_ata.to_account_info(),
+        authority: ladder.to_account_info(),
+        mint: ctx.accounts.token_mint_in.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint_in.decimals)?;
+
+    emit!(LadderLevelTaken {
+        ladder: ladder.key(),
+        depositor,
+        taker: ctx.accounts.taker.key(),
+        level,
+        price: entry.price,
+        amount_in: amount,
+        amount_out,
+    });
+
+    // Ladder levels have fixed prices, so the pair's last fill price is kept
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(ladder.token_mint_in, ladder.token_mint_out, ctx.bumps.stats);
+    stats.record_volume(amount, amount_out);
+    ctx.accounts.taker_trader.record_volume(notional, now);
+    ctx.accounts.depositor_trader.record_volume(notional, now);
+
+    ctx.accounts.ladder.levels[level as usize].amount -= amount;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelLadder<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = depositor,
+        has_one = depositor,
+        has_one = token_mint_in,
+        has_one = vault,
+        seeds = [b"ladder", depositor.key().as_ref(), ladder.ladder_id.to_le_bytes().as_ref()],
+        bump = ladder.bump
+    )]
+    pub ladder: Box<Account<'info, Ladder>>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", ladder.token_mint_in.as_ref(), ladder.token_mint_out.as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Returns what is left on every level to the depositor and closes the
+/// vault and the ladder.
+pub fn process_cancel_ladder(ctx: Context<CancelLadder>) -> Result<()> {
+    let ladder = &ctx.accounts.ladder;
+    let depositor = ladder.depositor;
+    let ladder_id = ladder.ladder_id.to_le_bytes();
+    let ladder_seeds = &[
+        b"ladder".as_ref(),
+        depositor.as_ref(),
+        ladder_id.as_ref(),
+        &[ladder.bump],
+    ];
+    let signer_seeds = &[&ladder_seeds[..]];
+
+    let amount = ctx.accounts.vault.amount;
+    if amount > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.depositor_token_account.to_account_info(),
+            authority: ladder.to_account_info(),
+            mint: ctx.accounts.token_mint_in.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint_in.decimals)?;
+    }
+
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.depositor.to_account_info(),
+        authority: ladder.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    close_account(cpi_ctx)?;
+
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(ladder.token_mint_in, ladder.token_mint_out, ctx.bumps.stats);
+    stats.record_cancelled(ladder.remaining_amount());
+
+    msg!("Ladder cancelled: {} returned", amount);
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+use crate::{error::ErrorCode, program::Solswap, Config, ConfigV1, Offer, OfferV1, OFFER_VERSION};
+
+#[derive(Accounts)]
+pub struct MigrateOffer<'info> {
+    /// Anyone can migrate an offer; they fund the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: legacy offer that no longer deserializes as `Offer`; owner and
+    /// discriminator are checked before decoding
+    #[account(mut, owner = crate::ID)]
+    pub offer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_migrate_offer(ctx: Context<MigrateOffer>) -> Result<()> {
+    let info = ctx.accounts.offer.to_account_info();
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        check_discriminator(&data, Offer::DISCRIMINATOR)?;
+        // v2 offers have the current size and are told apart by their version byte
+        if data.len() == 8 + Offer::INIT_SPACE {
+            require!(data[8] != OFFER_VERSION, ErrorCode::AlreadyMigrated);
+            OfferV1::decode_v2(&data[8..])?
+        } else {
+            OfferV1::decode(&data[8..])?
+        }
+    };
+
+    let offer = legacy.upgrade();
+    write_upgraded(
+        &info,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        8 + Offer::INIT_SPACE,
+        &offer,
+    )?;
+
+    msg!("Offer {} migrated to v{}", info.key(), offer.version);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// Config admin, or the program upgrade authority for configs that predate the admin
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: legacy config that no longer deserializes as `Config`; owner and
+    /// discriminator are checked before decoding
+    #[account(mut, owner = crate::ID, seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Solswap>,
+
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let info = ctx.accounts.config.to_account_info();
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        check_discriminator(&data, Config::DISCRIMINATOR)?;
+        require!(
+            data.len() != 8 + Config::INIT_SPACE,
+            ErrorCode::AlreadyMigrated
+        );
+        ConfigV1::decode(&data[8..])?
+    };
+
+    let authority = ctx.accounts.authority.key();
+    let is_upgrade_authority =
+        ctx.accounts.program_data.upgrade_authority_address == Some(authority);
+    require!(
+        legacy.admin == authority || is_upgrade_authority,
+        ErrorCode::Unauthorized
+    );
+
+    // Configs from before the admin existed are claimed by whoever migrates them
+    let admin = match legacy.admin {
+        admin if admin == Pubkey::default() => authority,
+        admin => admin,
+    };
+
+    let config = legacy.upgrade(admin);
+    write_upgraded(
+        &info,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        8 + Config::INIT_SPACE,
+        &config,
+    )?;
+
+    msg!("Config migrated to v{}", config.version);
+    Ok(())
+}
+
+/// Legacy accounts keep their discriminator; only the data after it changed.
+fn check_discriminator(data: &[u8], discriminator: &[u8]) -> Result<()> {
+    require!(
+        data.len() >= discriminator.len() && &data[..discriminator.len()] == discriminator,
+        ErrorCode::InvalidLegacyAccount
+    );
+    Ok(())
+}
+
+/// Tops up rent, resizes the account and writes the upgraded layout in place.
+fn write_upgraded<'info, T: AccountSerialize>(
+    info: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+    account: &T,
+) -> Result<()> {
+    let shortfall = rent_shortfall(&Rent::get()?, info.lamports(), new_len);
+    if shortfall > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        transfer(cpi_ctx, shortfall)?;
+    }
+
+    info.resize(new_len)?;
+
+    let mut data = info.try_borrow_mut_data()?;
+    write_layout(&mut data, account)
+}
+
+/// Lamports an account holding `lamports` needs to stay rent exempt at `new_len`.
+fn rent_shortfall(rent: &Rent, lamports: u64, new_len: usize) -> u64 {
+    rent.minimum_balance(new_len).saturating_sub(lamports)
+}
+
+/// Serializes `account`, discriminator included, over the start of `data`.
+fn write_layout<T: AccountSerialize>(data: &mut [u8], account: &T) -> Result<()> {
+    let mut writer: &mut [u8] = data;
+    account.try_serialize(&mut writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// v1 offer account: discriminator, then the fields it had before
+    /// auctions, triggers and DCA were added.
+    fn v1_offer_account() -> Vec<u8> {
+        let mut data = Offer::DISCRIMINATOR.to_vec();
+        for byte in 1..=2 {
+            Pubkey::new_from_array([byte; 32])
+                .serialize(&mut data)
+                .unwrap();
+        }
+        500u64.serialize(&mut data).unwrap();
+        Pubkey::new_from_array([3; 32])
+            .serialize(&mut data)
+            .unwrap();
+        Pubkey::new_from_array([4; 32])
+            .serialize(&mut data)
+            .unwrap();
+        254u8.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn rewrites_v1_offer_to_the_current_layout() {
+        let rent = Rent::default();
+        let mut data = v1_offer_account();
+        let lamports = rent.minimum_balance(data.len());
+        let new_len = 8 + Offer::INIT_SPACE;
+
+        check_discriminator(&data, Offer::DISCRIMINATOR).unwrap();
+        let offer = OfferV1::decode(&data[8..]).unwrap().upgrade();
+
+        // The payer covers exactly the rent of the added bytes
+        let shortfall = rent_shortfall(&rent, lamports, new_len);
+        assert_eq!(lamports + shortfall, rent.minimum_balance(new_len));
+        assert_eq!(rent_shortfall(&rent, lamports + shortfall, new_len), 0);
+
+        // `resize` zero-fills the new bytes
+        data.resize(new_len, 0);
+        write_layout(&mut data, &offer).unwrap();
+
+        assert_eq!(data.len(), new_len);
+        let migrated = Offer::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.version, OFFER_VERSION);
+        assert_eq!(migrated.token_mint_in, Pubkey::new_from_array([1; 32]));
+        assert_eq!(migrated.token_mint_out, Pubkey::new_from_array([2; 32]));
+        assert_eq!(migrated.deposited_amount, 500);
+        assert_eq!(migrated.depositor, Pubkey::new_from_array([3; 32]));
+        assert_eq!(migrated.vault, Pubkey::new_from_array([4; 32]));
+        assert_eq!(migrated.bump, 254);
+
+        // A second migration sees the current size and version
+        assert_eq!(data[8], OFFER_VERSION);
+    }
+
+    #[test]
+    fn rejects_a_too_small_buffer() {
+        let offer = OfferV1::decode(&v1_offer_account()[8..]).unwrap().upgrade();
+        let mut data = vec![0; 8 + 32];
+
+        assert!(write_layout(&mut data, &offer).is_err());
+    }
+}
@@ -1,11 +1,71 @@
 pub mod deposit;
 pub use deposit::*;
 
+pub mod amend_offer;
+pub use amend_offer::*;
+
+pub mod cancel_offer;
+pub use cancel_offer::*;
+
+pub mod delegate;
+pub use delegate::*;
+
 pub mod take_offer;
 pub use take_offer::*;
 
 pub mod init_config;
 pub use init_config::*;
 
+pub mod execute_trigger;
+pub use execute_trigger::*;
+
+pub mod flash_take_offer;
+pub use flash_take_offer::*;
+
+pub mod take_offers_batch;
+pub use take_offers_batch::*;
+
+pub mod allowlist;
+pub use allowlist::*;
+
+pub mod ladder;
+pub use ladder::*;
+
+pub mod grid;
+pub use grid::*;
+
+pub mod position;
+pub use position::*;
+
+pub mod vesting;
+pub use vesting::*;
+
+pub mod basket;
+pub use basket::*;
+
+pub mod take_barter;
+pub use take_barter::*;
+
+pub mod commit_take;
+pub use commit_take::*;
+
+pub mod quote_take;
+pub use quote_take::*;
+
+pub mod fill_quote;
+pub use fill_quote::*;
+
+pub mod set_price_feed;
+pub use set_price_feed::*;
+
+pub mod set_mint_limits;
+pub use set_mint_limits::*;
+
+pub mod migrate;
+pub use migrate::*;
+
+pub mod fees;
+pub use fees::*;
+
 pub mod utils;
 pub use utils::*;
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        mint_to, set_authority, spl_token_2022::instruction::AuthorityType, Mint, MintTo,
+        SetAuthority, TokenAccount, TokenInterface,
+    },
+};
+
+use crate::{error::ErrorCode, Offer, PositionMinted};
+
+#[derive(Accounts)]
+pub struct MintOfferPosition<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = depositor,
+        seeds = [offer.token_mint_in.as_ref(), depositor.key().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Fresh keypair; its supply is fixed at one once minted
+    #[account(
+        init,
+        payer = depositor,
+        mint::decimals = 0,
+        mint::authority = offer,
+        mint::token_program = token_program,
+    )]
+    pub position_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = depositor,
+        associated_token::mint = position_mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_position_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Mints the offer's position NFT to the depositor. From then on whoever
+/// holds it receives the proceeds and is the only one who can cancel.
+///
+/// This is a separate step from `create_offer`, which is already at its
+/// account and argument limits; send both in one transaction to create a
+/// tokenized offer atomically.
+pub fn process_mint_offer_position(ctx: Context<MintOfferPosition>) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    require!(offer.position_mint.is_none(), ErrorCode::AlreadyTokenized);
+    // Grid proceeds are reposted for the depositor and never reach a holder
+    require!(offer.grid.is_none(), ErrorCode::GridNotSupported);
+
+    let token_mint_in = offer.token_mint_in;
+    let depositor = ctx.accounts.depositor.key();
+    let offer_seeds = &[token_mint_in.as_ref(), depositor.as_ref(), &[offer.bump]];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.position_mint.to_account_info(),
+        to: ctx.accounts.depositor_position_account.to_account_info(),
+        authority: offer.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    mint_to(cpi_ctx, 1)?;
+
+    let cpi_accounts = SetAuthority {
+        current_authority: offer.to_account_info(),
+        account_or_mint: ctx.accounts.position_mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    set_authority(cpi_ctx, AuthorityType::MintTokens, None)?;
+
+    let position_mint = ctx.accounts.position_mint.key();
+    ctx.accounts.offer.position_mint = Some(position_mint);
+
+    emit!(PositionMinted {
+        offer: ctx.accounts.offer.key(),
+        depositor,
+        position_mint,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+
+use crate::{
+    error::ErrorCode, Config, MintLimits, Offer, PairBreaker, Quote, TraderAccount, FEED_ID,
+    MAXIMUM_AGE,
+};
+
+#[derive(Accounts)]
+pub struct QuoteTake<'info> {
+    /// CHECK: wallet the quote is for; only used to derive its trader PDA
+    pub taker: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub offer: Account<'info, Offer>,
+
+    /// CHECK: limits PDA for the offer's `token_mint_in`; may be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", offer.token_mint_in.as_ref()],
+        bump
+    )]
+    pub mint_limits: UncheckedAccount<'info>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    /// Taker's trader account, for their fee tier; omit if they have none
+    #[account(
+        seeds = [b"trader", taker.key().as_ref()],
+        bump = taker_trader.bump
+    )]
+    pub taker_trader: Option<Account<'info, TraderAccount>>,
+
+    /// Depositor's trader account, for their rebate tier; omit if they have none
+    #[account(
+        seeds = [b"trader", offer.depositor.as_ref()],
+        bump = depositor_trader.bump
+    )]
+    pub depositor_trader: Option<Account<'info, TraderAccount>>,
+
+    /// CHECK: breaker PDA of the offer's pair; may be uninitialized before its first fill
+    #[account(
+        seeds = [
+            b"breaker",
+            offer.token_mint_in.min(offer.token_mint_out).as_ref(),
+            offer.token_mint_in.max(offer.token_mint_out).as_ref(),
+        ],
+        bump
+    )]
+    pub breaker: UncheckedAccount<'info>,
+}
+
+/// Prices a fill of `amount` right now, with the same amount and circuit
+/// breaker checks as `take_offer` and `reveal_take`, without moving any
+/// funds. The result is returned through `set_return_data`.
+pub fn process_quote_take(
+    ctx: Context<QuoteTake>,
+    amount: u64,
+    referral_bps: u16,
+) -> Result<Quote> {
+    require!(
+        referral_bps <= ctx.accounts.config.max_referral_bps,
+        ErrorCode::InvalidReferralBps
+    );
+
+    let clock = Clock::get()?;
+    let price = ctx
+        .accounts
+        .price_update
+        .get_price_no_older_than(&clock, MAXIMUM_AGE, &get_feed_id_from_hex(FEED_ID)?)?
+        .price;
+
+    let offer = &ctx.accounts.offer;
+    let now = clock.unix_timestamp;
+    require!(offer.is_fillable(price, now), ErrorCode::OfferNotFillable);
+    require!(amount > 0, ErrorCode::AmountZero);
+    require!(
+        amount <= offer.available_amount(now),
+        ErrorCode::AmountTooLarge
+    );
+    // Partial fills must not leave a residual below the minimum offer size
+    if let Some(limits) = MintLimits::load(&ctx.accounts.mint_limits)? {
+        require!(
+            limits.max_fill(offer.deposited_amount, amount) == amount,
+            ErrorCode::AmountTooSmall
+        );
+    }
+    if offer.dca.is_some() {
+        require!(
+            amount == offer.available_amount(now),
+            ErrorCode::PartialTranche
+        );
+    }
+
+    let volume = |trader: &Option<Account<TraderAccount>>| {
+        trader
+            .as_ref()
+            .map_or(0, |trader| trader.rolling_volume(now))
+    };
+    let fees = ctx.accounts.config.fee_rates(
+        volume(&ctx.accounts.taker_trader),
+        volume(&ctx.accounts.depositor_trader),
+    );
+
+    let quote = offer.quote(amount, price, now, referral_bps, fees)?;
+
+    let circuit_breaker = &ctx.accounts.config.circuit_breaker;
+    let notional = offer.usdc_notional(amount, quote.amount_out);
+    match PairBreaker::load(&ctx.accounts.breaker)? {
+        Some(breaker) => breaker.check_fill(circuit_breaker, price, notional, now)?,
+        None => circuit_breaker.check_notional(0, notional)?,
+    }
+
+    msg!(
+        "Quote: {} in for {} out at price {}",
+        quote.amount_in,
+        quote.amount_out,
+        price
+    );
+
+    Ok(quote)
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{error::ErrorCode, Config, MintLimits};
+
+#[derive(Accounts)]
+pub struct SetMintLimits<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MintLimits::INIT_SPACE,
+        seeds = [b"mint_limits", mint.key().as_ref()],
+        bump
+    )]
+    pub mint_limits: Account<'info, MintLimits>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_set_mint_limits(
+    ctx: Context<SetMintLimits>,
+    min_amount: u64,
+    max_amount: u64,
+) -> Result<()> {
+    require!(
+        min_amount > 0 && min_amount <= max_amount,
+        ErrorCode::InvalidMintLimits
+    );
+
+    *ctx.accounts.mint_limits = MintLimits {
+        mint: ctx.accounts.mint.key(),
+        min_amount,
+        max_amount,
+        bump: ctx.bumps.mint_limits,
+    };
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{error::ErrorCode, Config, PriceFeed};
+
+#[derive(Accounts)]
+pub struct SetPriceFeed<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PriceFeed::INIT_SPACE,
+        seeds = [b"price_feed", mint.key().as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_set_price_feed(ctx: Context<SetPriceFeed>, feed_id: [u8; 32]) -> Result<()> {
+    *ctx.accounts.price_feed = PriceFeed {
+        mint: ctx.accounts.mint.key(),
+        feed_id,
+        bump: ctx.bumps.price_feed,
+    };
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use super::pay_protocol_fee;
+use crate::{error::ErrorCode, Config, Offer, OfferTaken, Stats, TraderAccount};
+
+#[derive(Accounts)]
+pub struct TakeBarter<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: any wallet chosen by the taker to receive the offered tokens
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_in_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_out_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: the depositor, or the holder of the offer's position NFT; checked in the handler
+    pub proceeds_owner: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = proceeds_owner,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_receive_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = taker,
+        has_one = depositor,
+        has_one = token_mint_in,
+        has_one = token_mint_out,
+        seeds = [token_mint_in.key().as_ref(), depositor.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Taker's token account of the gate mint, required for token-gated offers
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holder's account of the position NFT, required for tokenized offers
+    pub position_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", taker.key().as_ref()],
+        bump
+    )]
+    pub taker_trader: Box<Account<'info, TraderAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", depositor.key().as_ref()],
+        bump
+    )]
+    pub depositor_trader: Box<Account<'info, TraderAccount>>,
+
+    /// Admin token account receiving the protocol share of the taker fee
+    #[account(
+        mut,
+        token::mint = token_mint_out,
+        token::token_program = token_program,
+        constraint = fee_token_account.owner == config.admin @ ErrorCode::Unauthorized,
+    )]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Swaps the whole deposit of a barter offer for the exact amount the
+/// depositor asked for, paid to the depositor or the position holder. No
+/// oracle is involved. Fails unless the deposit is `expected_amount_in` and
+/// the price at most `max_amount_out`. The taker pays their fee tier on top;
+/// only barters with a USDC side add to the traders' volume.
+pub fn process_take_barter(
+    ctx: Context<TakeBarter>,
+    expected_amount_in: u64,
+    max_amount_out: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    offer.check_taker_allowed(ctx.accounts.taker.key, &proof)?;
+    offer.check_taker_gate(
+        ctx.accounts.taker.key,
+        ctx.accounts.gate_token_account.as_deref(),
+    )?;
+    offer.check_recipient(ctx.accounts.taker.key, ctx.accounts.recipient.key)?;
+    let amount_out = offer.barter_amount_out.ok_or(ErrorCode::NotABarterOffer)?;
+    let amount_in = offer.deposited_amount;
+    require!(
+        amount_in == expected_amount_in && amount_out <= max_amount_out,
+        ErrorCode::BarterTermsChanged
+    );
+    offer.check_proceeds_owner(
+        ctx.accounts.proceeds_owner.key,
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+
+    msg!("Barter | {} in for {} out", amount_in, amount_out);
+
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts
+        .taker_trader
+        .init_if_empty(ctx.accounts.taker.key(), ctx.bumps.taker_trader);
+    ctx.accounts
+        .depositor_trader
+        .init_if_empty(ctx.accounts.depositor.key(), ctx.bumps.depositor_trader);
+    let fees = ctx.accounts.config.fee_rates(
+        ctx.accounts.taker_trader.rolling_volume(now),
+        ctx.accounts.depositor_trader.rolling_volume(now),
+    );
+    let (taker_fee, maker_rebate) = fees.fees_on(amount_out);
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.taker_token_out_ata.to_account_info(),
+        to: ctx.accounts.depositor_receive_ata.to_account_info(),
+        authority: ctx.accounts.taker.to_account_info(),
+        mint: ctx.accounts.token_mint_out.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    // The maker rebate is carved from the taker fee and paid with the price
+    transfer_checked(
+        cpi_ctx,
+        amount_out + maker_rebate,
+        ctx.accounts.token_mint_out.decimals,
+    )?;
+    pay_protocol_fee(
+        taker_fee - maker_rebate,
+        ctx.accounts.taker_token_out_ata.to_account_info(),
+        ctx.accounts.taker.to_account_info(),
+        ctx.accounts.fee_token_account.as_ref(),
+        &ctx.accounts.token_mint_out,
+        &ctx.accounts.token_program,
+    )?;
+
+    let token_mint_in = ctx.accounts.token_mint_in.key();
+    let depositor = ctx.accounts.depositor.key();
+    let offer_seeds = &[token_mint_in.as_ref(), depositor.as_ref(), &[offer.bump]];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.taker_token_in_ata.to_account_info(),
+        authority: offer.to_account_info(),
+        mint: ctx.accounts.token_mint_in.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    transfer_checked(cpi_ctx, amount_in, ctx.accounts.token_mint_in.decimals)?;
+
+    emit!(OfferTaken {
+        offer: offer.key(),
+        depositor,
+        taker: ctx.accounts.taker.key(),
+        token_mint_in,
+        token_mint_out: offer.token_mint_out,
+        amount_in,
+        amount_out,
+        referrer: None,
+        referral_amount: 0,
+        client_order_id: offer.client_order_id,
+    });
+
+    // Barter fills have no oracle price, so the pair's last fill price is kept
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(token_mint_in, offer.token_mint_out, ctx.bumps.stats);
+    stats.record_volume(amount_in, amount_out);
+
+    let usdc_mint = ctx.accounts.config.usdc_mint;
+    let notional = if token_mint_in == usdc_mint {
+        amount_in
+    } else if offer.token_mint_out == usdc_mint {
+        amount_out
+    } else {
+        0
+    };
+    ctx.accounts.taker_trader.record_volume(notional, now);
+    ctx.accounts.depositor_trader.record_volume(notional, now);
+
+    Ok(())
+}
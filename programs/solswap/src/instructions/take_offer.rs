@@ -6,7 +6,11 @@ use anchor_spl::{
 };
 use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
 
-use crate::{lamports_to_usdc, usdc_to_lamports, Offer, FEED_ID, MAXIMUM_AGE};
+use super::pay_protocol_fee;
+use crate::{
+    error::ErrorCode, Config, MintLimits, Offer, OfferTaken, PairBreaker, Quote, Stats,
+    TraderAccount, VestingEscrow, VestingParams, FEED_ID, MAXIMUM_AGE,
+};
 
 #[derive(Accounts)]
 pub struct TakeOffer<'info> {
@@ -15,14 +19,23 @@ pub struct TakeOffer<'info> {
 
     pub depositor: SystemAccount<'info>,
 
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
     pub token_mint_in: InterfaceAccount<'info, Mint>,
     pub token_mint_out: InterfaceAccount<'info, Mint>,
 
+    /// CHECK: any wallet chosen by the taker to receive the offered tokens
+    pub recipient: UncheckedAccount<'info>,
+
     #[account(
         init_if_needed,
         payer = taker,
         associated_token::mint = token_mint_in,
-        associated_token::authority = taker,
+        associated_token::authority = recipient,
         associated_token::token_program = token_program,
     )]
     pub taker_token_in_ata: InterfaceAccount<'info, TokenAccount>,
@@ -35,18 +48,20 @@ pub struct TakeOffer<'info> {
     )]
     pub taker_token_out_ata: InterfaceAccount<'info, TokenAccount>,
 
+    /// CHECK: the depositor, or the holder of the offer's position NFT; checked in the handler
+    pub proceeds_owner: UncheckedAccount<'info>,
+
     #[account(
         init_if_needed,
         payer = taker,
         associated_token::mint = token_mint_out,
-        associated_token::authority = depositor,
+        associated_token::authority = proceeds_owner,
         associated_token::token_program = token_program,
     )]
     pub depositor_receive_ata: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        close = taker,
         has_one = depositor,
         has_one = token_mint_in,
         has_one = token_mint_out,
@@ -66,98 +81,394 @@ pub struct TakeOffer<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + PairBreaker::INIT_SPACE,
+        seeds = [
+            b"breaker",
+            token_mint_in.key().min(token_mint_out.key()).as_ref(),
+            token_mint_in.key().max(token_mint_out.key()).as_ref(),
+        ],
+        bump
+    )]
+    pub breaker: Box<Account<'info, PairBreaker>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", taker.key().as_ref()],
+        bump
+    )]
+    pub taker_trader: Box<Account<'info, TraderAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", depositor.key().as_ref()],
+        bump
+    )]
+    pub depositor_trader: Box<Account<'info, TraderAccount>>,
+
     pub price_update: Account<'info, PriceUpdateV2>,
 
+    /// Frontend or aggregator token account receiving the referral share
+    #[account(
+        mut,
+        token::mint = token_mint_in,
+        token::token_program = token_program,
+    )]
+    pub referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Admin token account receiving the protocol share of the taker fee
+    #[account(
+        mut,
+        token::mint = token_mint_out,
+        token::token_program = token_program,
+        constraint = fee_token_account.owner == config.admin @ ErrorCode::Unauthorized,
+    )]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker's token account of the gate mint, required for token-gated offers
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holder's account of the position NFT, required for tokenized offers
+    pub position_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required for offers with a vesting schedule
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + VestingEscrow::INIT_SPACE,
+        seeds = [b"vesting", offer.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub vesting_escrow: Option<Box<Account<'info, VestingEscrow>>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = vesting_escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vesting_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Depositor's offer on the other side of the pair, created by
+    /// `set_offer_grid`; required for grid offers
+    #[account(
+        mut,
+        has_one = depositor,
+        seeds = [token_mint_out.key().as_ref(), depositor.key().as_ref()],
+        bump = reverse_offer.bump
+    )]
+    pub reverse_offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = reverse_offer,
+        associated_token::token_program = token_program,
+    )]
+    pub reverse_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_out.key().as_ref(), token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub reverse_stats: Option<Box<Account<'info, Stats>>>,
+
+    /// CHECK: limits PDA for `token_mint_out`, capping the reverse offer; may
+    /// be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub reverse_mint_limits: Option<UncheckedAccount<'info>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn process_take_offer(ctx: Context<TakeOffer>) -> Result<()> {
-    let price_update = &ctx.accounts.price_update;
-    let price_info = price_update.get_price_no_older_than(
-        &Clock::get()?,
-        MAXIMUM_AGE,
-        &get_feed_id_from_hex(FEED_ID)?,
+pub fn process_take_offer(
+    ctx: Context<TakeOffer>,
+    referral_bps: u16,
+    proof: Vec<[u8; 32]>,
+    expected_vesting: Option<VestingParams>,
+) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    offer.check_vesting(expected_vesting)?;
+    offer.check_taker_allowed(ctx.accounts.taker.key, &proof)?;
+    offer.check_taker_gate(
+        ctx.accounts.taker.key,
+        ctx.accounts.gate_token_account.as_deref(),
     )?;
+    offer.check_recipient(ctx.accounts.taker.key, ctx.accounts.recipient.key)?;
 
-    let price = price_info.price;
-    msg!("Current price: {}", price);
+    let clock = Clock::get()?;
+    let price = ctx.accounts.oracle_price(&clock)?;
+    let now = clock.unix_timestamp;
 
-    let offer = &ctx.accounts.offer;
-    let taker = &ctx.accounts.taker;
-    let token_program = &ctx.accounts.token_program;
-
-    let depositor_gave_usdc =
-        offer.token_mint_in != pubkey!("So11111111111111111111111111111111111111112");
-
-    if depositor_gave_usdc {
-        // depositor deposited USDC, taker sends SOL
-        let lamports_amount = usdc_to_lamports(offer.deposited_amount, price);
-        msg!(
-            "USDC -> SOL | {} USDC ≈ {} lamports",
-            offer.deposited_amount,
-            lamports_amount
+    // DCA offers only release their current tranche; everything else is taken whole
+    let fill_amount = ctx.accounts.offer.available_amount(now);
+
+    ctx.accounts
+        .fill(fill_amount, price, now, referral_bps, &ctx.bumps)?;
+    Ok(())
+}
+
+impl<'info> TakeOffer<'info> {
+    pub fn oracle_price(&self, clock: &Clock) -> Result<i64> {
+        let price = self
+            .price_update
+            .get_price_no_older_than(clock, MAXIMUM_AGE, &get_feed_id_from_hex(FEED_ID)?)?
+            .price;
+        msg!("Current price: {}", price);
+        Ok(price)
+    }
+
+    /// Fills `fill_amount` of the offer at `price`: the taker pays the
+    /// depositor, or the position holder, plus the fee for their volume tier, the referrer gets its
+    /// share and the taker the rest.
+    pub fn fill(
+        &mut self,
+        fill_amount: u64,
+        price: i64,
+        now: i64,
+        referral_bps: u16,
+        bumps: &TakeOfferBumps,
+    ) -> Result<Quote> {
+        require!(
+            referral_bps <= self.config.max_referral_bps,
+            ErrorCode::InvalidReferralBps
         );
 
+        let offer = &self.offer;
+        let taker = &self.taker;
+        let token_program = &self.token_program;
+
+        require!(offer.is_fillable(price, now), ErrorCode::OfferNotFillable);
+        offer.check_proceeds_owner(
+            self.proceeds_owner.key,
+            self.position_token_account.as_deref(),
+        )?;
+        require!(fill_amount > 0, ErrorCode::AmountZero);
+        require!(
+            fill_amount <= offer.available_amount(now),
+            ErrorCode::AmountTooLarge
+        );
+
+        // The referral share is carved from the tokens the taker receives
+        let referral_bps = match self.referrer_token_account {
+            Some(_) => referral_bps,
+            None => 0,
+        };
+
+        self.taker_trader
+            .init_if_empty(taker.key(), bumps.taker_trader);
+        self.depositor_trader
+            .init_if_empty(offer.depositor, bumps.depositor_trader);
+        let fees = self.config.fee_rates(
+            self.taker_trader.rolling_volume(now),
+            self.depositor_trader.rolling_volume(now),
+        );
+
+        // Auction offers are priced at the point of their decay curve we are at now
+        let quote = offer.quote(fill_amount, price, now, referral_bps, fees)?;
+        let amount_out = quote.amount_out;
+        let referral_amount = quote.referral_amount;
+
+        if offer.depositor_gave_usdc() {
+            // depositor deposited USDC, taker sends SOL
+            msg!(
+                "USDC -> SOL | {} USDC ≈ {} lamports",
+                fill_amount,
+                amount_out
+            );
+        } else {
+            // depositor deposited SOL, taker sends USDC
+            msg!(
+                "SOL -> USDC | {} lamports ≈ {} USDC",
+                fill_amount,
+                amount_out
+            );
+        }
+
+        // Reject fills on oracle glitches or beyond the pair's volume cap before moving funds
+        let notional = offer.usdc_notional(fill_amount, amount_out);
+        self.breaker
+            .init_if_empty(offer.token_mint_in, offer.token_mint_out, bumps.breaker);
+        self.breaker
+            .guard_fill(&self.config.circuit_breaker, price, notional, now)?;
+        let stats = &mut self.stats;
+        stats.init_if_empty(offer.token_mint_in, offer.token_mint_out, bumps.stats);
+
+        // Grid offers pay into the depositor's reverse offer instead
+        let payment = amount_out + quote.maker_rebate;
+        let payment_destination = if offer.reposts_proceeds() {
+            let (
+                Some(reverse_offer),
+                Some(reverse_vault),
+                Some(reverse_stats),
+                Some(reverse_mint_limits),
+            ) = (
+                self.reverse_offer.as_mut(),
+                &self.reverse_vault,
+                self.reverse_stats.as_mut(),
+                &self.reverse_mint_limits,
+            )
+            else {
+                return err!(ErrorCode::MissingGridAccounts);
+            };
+            let reverse_amount = reverse_offer.deposited_amount;
+            let limits = MintLimits::load(reverse_mint_limits)?;
+            offer.repost_into(reverse_offer, payment, limits.as_ref())?;
+
+            reverse_stats.init_if_empty(
+                offer.token_mint_out,
+                offer.token_mint_in,
+                bumps.reverse_stats.ok_or(ErrorCode::MissingGridAccounts)?,
+            );
+            reverse_stats.record_amended(reverse_amount, reverse_offer.deposited_amount);
+            reverse_vault.to_account_info()
+        } else {
+            self.depositor_receive_ata.to_account_info()
+        };
+
         let cpi_accounts = TransferChecked {
-            from: ctx.accounts.taker_token_out_ata.to_account_info(),
-            to: ctx.accounts.depositor_receive_ata.to_account_info(),
+            from: self.taker_token_out_ata.to_account_info(),
+            to: payment_destination,
             authority: taker.to_account_info(),
-            mint: ctx.accounts.token_mint_out.to_account_info(),
+            mint: self.token_mint_out.to_account_info(),
         };
 
         let cpi_program = token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, lamports_amount, 9)?;
-    } else {
-        // depositor deposited SOL, taker sends USDC
-        let usdc_amount = lamports_to_usdc(offer.deposited_amount, price);
-        msg!(
-            "SOL -> USDC | {} lamports ≈ {} USDC",
-            offer.deposited_amount,
-            usdc_amount
-        );
+        // The maker rebate is carved from the taker fee and paid with the price
+        transfer_checked(cpi_ctx, payment, self.token_mint_out.decimals)?;
+
+        pay_protocol_fee(
+            quote.taker_fee - quote.maker_rebate,
+            self.taker_token_out_ata.to_account_info(),
+            taker.to_account_info(),
+            self.fee_token_account.as_ref(),
+            &self.token_mint_out,
+            token_program,
+        )?;
+
+        let token_mint_in = self.token_mint_in.key();
+        let depositor = self.depositor.key();
+
+        let offer_seeds = &[
+            token_mint_in.as_ref(),
+            depositor.as_ref(),
+            &[self.offer.bump],
+        ];
+        let signer_seeds = &[&offer_seeds[..]];
+
+        if let Some(referrer_token_account) = &self.referrer_token_account {
+            if referral_amount > 0 {
+                let cpi_accounts = TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: referrer_token_account.to_account_info(),
+                    authority: self.offer.to_account_info(),
+                    mint: self.token_mint_in.to_account_info(),
+                };
+
+                let cpi_program = token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+                transfer_checked(cpi_ctx, referral_amount, self.token_mint_in.decimals)?;
+            }
+        }
+
+        // Transfer the offered tokens from the vault to the taker's recipient,
+        // or to its vesting escrow if the offer releases them over time
+        let destination = match offer.vesting {
+            Some(vesting) => {
+                let (Some(escrow), Some(vesting_vault)) =
+                    (self.vesting_escrow.as_mut(), &self.vesting_vault)
+                else {
+                    return err!(ErrorCode::MissingVestingAccounts);
+                };
+                if escrow.beneficiary == Pubkey::default() {
+                    escrow.set_inner(VestingEscrow {
+                        beneficiary: self.recipient.key(),
+                        offer: offer.key(),
+                        mint: offer.token_mint_in,
+                        total: 0,
+                        claimed: 0,
+                        start: now,
+                        vested_at_start: 0,
+                        cliff: vesting.cliff,
+                        duration: vesting.duration,
+                        bump: bumps
+                            .vesting_escrow
+                            .ok_or(ErrorCode::MissingVestingAccounts)?,
+                    });
+                }
+                escrow.top_up(quote.taker_receives, vesting, now);
+                vesting_vault.to_account_info()
+            }
+            None => self.taker_token_in_ata.to_account_info(),
+        };
 
         let cpi_accounts = TransferChecked {
-            from: ctx.accounts.taker_token_out_ata.to_account_info(),
-            to: ctx.accounts.depositor_receive_ata.to_account_info(),
-            authority: taker.to_account_info(),
-            mint: ctx.accounts.token_mint_out.to_account_info(),
+            from: self.vault.to_account_info(),
+            to: destination,
+            authority: self.offer.to_account_info(),
+            mint: self.token_mint_in.to_account_info(),
         };
 
         let cpi_program = token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
-        transfer_checked(cpi_ctx, usdc_amount, 6)?;
-    }
+        transfer_checked(cpi_ctx, quote.taker_receives, self.token_mint_in.decimals)?;
 
-    // Transfer the offered tokens from the vault to the taker
-    let cpi_accounts = TransferChecked {
-        from: ctx.accounts.vault.to_account_info(),
-        to: ctx.accounts.taker_token_in_ata.to_account_info(),
-        authority: ctx.accounts.offer.to_account_info(),
-        mint: ctx.accounts.token_mint_in.to_account_info(),
-    };
-
-    let cpi_program = token_program.to_account_info();
-    let token_mint_in = ctx.accounts.token_mint_in.key();
-    let depositor = ctx.accounts.depositor.key();
-
-    let offer_seeds = &[
-        token_mint_in.as_ref(),
-        depositor.as_ref(),
-        &[ctx.accounts.offer.bump],
-    ];
-    let signer_seeds = &[&offer_seeds[..]];
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-
-    transfer_checked(
-        cpi_ctx,
-        offer.deposited_amount,
-        ctx.accounts.token_mint_in.decimals,
-    )?;
+        emit!(OfferTaken {
+            offer: offer.key(),
+            depositor,
+            taker: taker.key(),
+            token_mint_in,
+            token_mint_out: offer.token_mint_out,
+            amount_in: fill_amount,
+            amount_out,
+            referrer: self
+                .referrer_token_account
+                .as_ref()
+                .map(|account| account.owner),
+            referral_amount,
+            client_order_id: offer.client_order_id,
+        });
 
-    Ok(())
+        stats.record_fill(fill_amount, amount_out, price, now);
+        self.taker_trader.record_volume(notional, now);
+        self.depositor_trader.record_volume(notional, now);
+
+        // Fully taken offers are closed and their rent goes to the taker. Grid
+        // offers stay open to be refilled and are closed by `cancel_grid`.
+        let offer = &mut self.offer;
+        offer.record_fill(fill_amount, now);
+        if offer.deposited_amount == 0 && offer.grid.is_none() {
+            offer.close(self.taker.to_account_info())?;
+        }
+
+        Ok(quote)
+    }
 }
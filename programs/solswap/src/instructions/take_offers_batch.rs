@@ -0,0 +1,382 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{allocate, assign, transfer, Allocate, Assign, Transfer};
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+
+use super::pay_protocol_fee;
+use crate::{
+    error::ErrorCode, Config, MintLimits, Offer, PairBreaker, Stats, TraderAccount, FEED_ID,
+    MAXIMUM_AGE,
+};
+
+/// Accounts each offer contributes to `remaining_accounts`, in order:
+/// offer, vault, depositor_receive_ata, depositor_trader.
+pub const BATCH_ACCOUNTS_PER_OFFER: usize = 4;
+
+#[derive(Accounts)]
+pub struct TakeOffersBatch<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: limits PDA for `token_mint_in`; may be uninitialized if the admin set none
+    #[account(
+        seeds = [b"mint_limits", token_mint_in.key().as_ref()],
+        bump
+    )]
+    pub mint_limits: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_in,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_in_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_out,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_out_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [b"stats", token_mint_in.key().as_ref(), token_mint_out.key().as_ref()],
+        bump
+    )]
+    pub stats: Box<Account<'info, Stats>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + PairBreaker::INIT_SPACE,
+        seeds = [
+            b"breaker",
+            token_mint_in.key().min(token_mint_out.key()).as_ref(),
+            token_mint_in.key().max(token_mint_out.key()).as_ref(),
+        ],
+        bump
+    )]
+    pub breaker: Box<Account<'info, PairBreaker>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + TraderAccount::INIT_SPACE,
+        seeds = [b"trader", taker.key().as_ref()],
+        bump
+    )]
+    pub taker_trader: Box<Account<'info, TraderAccount>>,
+
+    /// Admin token account receiving the protocol share of the taker fees
+    #[account(
+        mut,
+        token::mint = token_mint_out,
+        token::token_program = token_program,
+        constraint = fee_token_account.owner == config.admin @ ErrorCode::Unauthorized,
+    )]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Fills the offers in `remaining_accounts` in order until `max_amount_in` of
+/// `token_mint_in` has been bought. Offers priced above `max_price` (in
+/// `token_mint_out` base units per whole `token_mint_in`, taker fee included)
+/// or no longer valid are skipped. Each fill charges the taker's fee tier and
+/// counts towards the taker's and the depositor's volume, like `take_offer`. The last offer touched may be partially filled, as long as the
+/// residual stays above the mint's minimum offer size; DCA tranches are only
+/// filled whole.
+pub fn process_take_offers_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeOffersBatch<'info>>,
+    max_amount_in: u64,
+    max_price: u64,
+) -> Result<()> {
+    require!(max_amount_in > 0, ErrorCode::AmountZero);
+    let batch = ctx
+        .remaining_accounts
+        .chunks_exact(BATCH_ACCOUNTS_PER_OFFER);
+    require!(
+        batch.remainder().is_empty(),
+        ErrorCode::InvalidBatchAccounts
+    );
+
+    // Read the oracle once for the whole batch
+    let clock = Clock::get()?;
+    let price = ctx
+        .accounts
+        .price_update
+        .get_price_no_older_than(&clock, MAXIMUM_AGE, &get_feed_id_from_hex(FEED_ID)?)?
+        .price;
+    msg!("Current price: {}", price);
+
+    let token_mint_in = ctx.accounts.token_mint_in.key();
+    let token_mint_out = ctx.accounts.token_mint_out.key();
+    let token_program = &ctx.accounts.token_program;
+    let unit_in = 10u128.pow(ctx.accounts.token_mint_in.decimals as u32);
+    let mint_limits = MintLimits::load(&ctx.accounts.mint_limits)?;
+
+    let stats = &mut ctx.accounts.stats;
+    stats.init_if_empty(token_mint_in, token_mint_out, ctx.bumps.stats);
+    let breaker = &mut ctx.accounts.breaker;
+    breaker.init_if_empty(token_mint_in, token_mint_out, ctx.bumps.breaker);
+    let taker_trader = &mut ctx.accounts.taker_trader;
+    taker_trader.init_if_empty(ctx.accounts.taker.key(), ctx.bumps.taker_trader);
+
+    let mut filled: u64 = 0;
+
+    for accounts in batch {
+        if filled == max_amount_in {
+            break;
+        }
+
+        let [offer_info, vault_info, depositor_receive_info, depositor_trader_info] = accounts
+        else {
+            unreachable!()
+        };
+
+        let mut offer = match Account::<Offer>::try_from(offer_info) {
+            Ok(offer) => offer,
+            Err(_) => {
+                msg!("Skipping {}: not an open offer", offer_info.key());
+                continue;
+            }
+        };
+
+        let expected_receive_ata = get_associated_token_address_with_program_id(
+            &offer.proceeds_wallet(),
+            &token_mint_out,
+            &token_program.key(),
+        );
+        if offer.token_mint_in != token_mint_in
+            || offer.token_mint_out != token_mint_out
+            || offer.vault != vault_info.key()
+            || depositor_receive_info.key() != expected_receive_ata
+            || depositor_receive_info.data_is_empty()
+        {
+            msg!("Skipping {}: accounts do not match", offer_info.key());
+            continue;
+        }
+
+        let now = clock.unix_timestamp;
+        if offer.vesting.is_some()
+            || offer.position_mint.is_some()
+            || offer.grid.is_some()
+            || offer.taker_merkle_root.is_some()
+            || offer.taker_gate.is_some()
+        {
+            msg!(
+                "Skipping {}: offers with vesting, a position, a grid or taker restrictions need take_offer",
+                offer_info.key()
+            );
+            continue;
+        }
+        if !offer.is_fillable(price, now) {
+            msg!("Skipping {}: not fillable now", offer_info.key());
+            continue;
+        }
+
+        // Partial fills must not leave a residual below the minimum offer size
+        let wanted = (max_amount_in - filled).min(offer.available_amount(now));
+        let fill_amount = match &mint_limits {
+            Some(limits) => limits.max_fill(offer.deposited_amount, wanted),
+            None => offer.deposited_amount.min(wanted),
+        };
+        if fill_amount == 0 {
+            msg!("Skipping {}: residual would be too small", offer_info.key());
+            continue;
+        }
+        // A partial fill would lock the rest of the tranche until the next interval
+        if offer.dca.is_some() && fill_amount < offer.available_amount(now) {
+            msg!(
+                "Skipping {}: DCA tranches are filled whole",
+                offer_info.key()
+            );
+            continue;
+        }
+
+        let Some(mut depositor_trader) = load_or_create_trader(
+            depositor_trader_info,
+            &offer.depositor,
+            &ctx.accounts.taker,
+            &ctx.accounts.system_program,
+        )?
+        else {
+            msg!(
+                "Skipping {}: not the depositor's trader account",
+                offer_info.key()
+            );
+            continue;
+        };
+        let fees = ctx.accounts.config.fee_rates(
+            taker_trader.rolling_volume(now),
+            depositor_trader.rolling_volume(now),
+        );
+
+        let quote = match offer.quote(fill_amount, price, now, 0, fees) {
+            Ok(quote) if quote.amount_out > 0 => quote,
+            _ => {
+                msg!("Skipping {}: cannot be priced", offer_info.key());
+                continue;
+            }
+        };
+        let amount_out = quote.amount_out;
+
+        let amount_paid = amount_out as u128 + quote.taker_fee as u128;
+        if amount_paid * unit_in > max_price as u128 * fill_amount as u128 {
+            msg!("Skipping {}: above max price", offer_info.key());
+            continue;
+        }
+
+        let notional = offer.usdc_notional(fill_amount, amount_out);
+        breaker.guard_fill(&ctx.accounts.config.circuit_breaker, price, notional, now)?;
+
+        msg!(
+            "Filling {} | {} in for {} out",
+            offer_info.key(),
+            fill_amount,
+            amount_out
+        );
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.taker_token_out_ata.to_account_info(),
+            to: depositor_receive_info.clone(),
+            authority: ctx.accounts.taker.to_account_info(),
+            mint: ctx.accounts.token_mint_out.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+        // The maker rebate is carved from the taker fee and paid with the price
+        transfer_checked(
+            cpi_ctx,
+            amount_out + quote.maker_rebate,
+            ctx.accounts.token_mint_out.decimals,
+        )?;
+        pay_protocol_fee(
+            quote.taker_fee - quote.maker_rebate,
+            ctx.accounts.taker_token_out_ata.to_account_info(),
+            ctx.accounts.taker.to_account_info(),
+            ctx.accounts.fee_token_account.as_ref(),
+            &ctx.accounts.token_mint_out,
+            token_program,
+        )?;
+
+        let cpi_accounts = TransferChecked {
+            from: vault_info.clone(),
+            to: ctx.accounts.taker_token_in_ata.to_account_info(),
+            authority: offer_info.clone(),
+            mint: ctx.accounts.token_mint_in.to_account_info(),
+        };
+        let offer_seeds = &[
+            token_mint_in.as_ref(),
+            offer.depositor.as_ref(),
+            &[offer.bump],
+        ];
+        let signer_seeds = &[&offer_seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, fill_amount, ctx.accounts.token_mint_in.decimals)?;
+
+        filled += fill_amount;
+        offer.record_fill(fill_amount, now);
+        stats.record_fill(fill_amount, amount_out, price, now);
+        taker_trader.record_volume(notional, now);
+        depositor_trader.record_volume(notional, now);
+        depositor_trader.exit(&crate::ID)?;
+
+        // Fully taken offers are closed like in take_offer; partial fills keep the residual
+        if offer.deposited_amount == 0 {
+            offer.close(ctx.accounts.taker.to_account_info())?;
+        } else {
+            offer.exit(&crate::ID)?;
+        }
+    }
+
+    msg!("Batch filled {} of {}", filled, max_amount_in);
+
+    Ok(())
+}
+
+/// Loads `wallet`'s trader account from `info`, creating it at the taker's
+/// expense the way `init_if_needed` would if the wallet has none yet.
+/// Returns `None` if `info` is not that wallet's trader PDA.
+fn load_or_create_trader<'info>(
+    info: &'info AccountInfo<'info>,
+    wallet: &Pubkey,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<Option<Account<'info, TraderAccount>>> {
+    if info.owner == &crate::ID {
+        return Ok(Account::<TraderAccount>::try_from(info)
+            .ok()
+            .filter(|trader| trader.wallet == *wallet));
+    }
+
+    let (address, bump) = Pubkey::find_program_address(&[b"trader", wallet.as_ref()], &crate::ID);
+    if info.key() != address {
+        return Ok(None);
+    }
+    let seeds: &[&[u8]] = &[b"trader", wallet.as_ref(), &[bump]];
+    let signer_seeds = &[seeds];
+
+    // Anyone may have sent lamports to the address, so top it up rather than create it
+    let space = 8 + TraderAccount::INIT_SPACE;
+    let shortfall = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(info.lamports());
+    if shortfall > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        transfer(cpi_ctx, shortfall)?;
+    }
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        Allocate {
+            account_to_allocate: info.clone(),
+        },
+        signer_seeds,
+    );
+    allocate(cpi_ctx, space as u64)?;
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        Assign {
+            account_to_assign: info.clone(),
+        },
+        signer_seeds,
+    );
+    assign(cpi_ctx, &crate::ID)?;
+
+    // Written out now so the account is valid even if the offer is then skipped
+    let mut trader = Account::<TraderAccount>::try_from_unchecked(info)?;
+    trader.init_if_empty(*wallet, bump);
+    trader.exit(&crate::ID)?;
+    Ok(Some(trader))
+}
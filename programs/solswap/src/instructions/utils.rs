@@ -1,5 +1,13 @@
+use anchor_lang::solana_program::keccak;
+
+use crate::BPS_DENOMINATOR;
+
 const SCALE: u128 = 100_000_000_000;
 
+pub fn bps_of(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
 pub fn usdc_to_lamports(usdc_base: u64, price: i64) -> u64 {
     assert!(price > 0);
     let num = (usdc_base as u128) * SCALE;
@@ -12,3 +20,104 @@ pub fn lamports_to_usdc(lamports: u64, price: i64) -> u64 {
     let num = (lamports as u128) * (price as u128);
     (num / SCALE) as u64 // floor
 }
+
+/// Decimals of the USD values basket legs are summed in.
+pub const USD_DECIMALS: i32 = 8;
+/// Decimals of USDC, in which trader and pair volume is counted.
+pub const USDC_DECIMALS: u8 = 6;
+
+fn scale_pow10(value: u128, exponent: i32) -> Option<u128> {
+    if exponent >= 0 {
+        value.checked_mul(10u128.checked_pow(exponent as u32)?)
+    } else {
+        Some(value / 10u128.checked_pow(exponent.unsigned_abs())?)
+    }
+}
+
+/// USD value, with `USD_DECIMALS` decimals, of `amount` base units of a mint
+/// with `decimals`, priced at `price * 10^exponent` USD per whole token.
+pub fn usd_value(amount: u64, decimals: u8, price: i64, exponent: i32) -> Option<u128> {
+    if price <= 0 {
+        return None;
+    }
+    let value = (amount as u128).checked_mul(price as u128)?;
+    scale_pow10(value, exponent + USD_DECIMALS - decimals as i32)
+}
+
+/// Base units of a mint with `decimals` worth `usd` (with `USD_DECIMALS`
+/// decimals) at `price * 10^exponent` USD per whole token. Rounds down.
+pub fn amount_for_usd(usd: u128, decimals: u8, price: i64, exponent: i32) -> Option<u64> {
+    if price <= 0 {
+        return None;
+    }
+    let scaled = scale_pow10(usd, decimals as i32 - exponent - USD_DECIMALS)?;
+    u64::try_from(scaled / price as u128).ok()
+}
+
+/// Whether `proof` links `leaf` to `root`, hashing each pair of nodes with
+/// keccak in sorted order so proofs need no left/right flags.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        keccak::hashv(&[&first, &second]).to_bytes()
+    });
+    computed == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(n: u8) -> [u8; 32] {
+        keccak::hash(&[n]).to_bytes()
+    }
+
+    fn parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        keccak::hashv(&[&first, &second]).to_bytes()
+    }
+
+    /// Root of a four-leaf tree and the proof for each of its leaves.
+    fn tree() -> ([u8; 32], [Vec<[u8; 32]>; 4]) {
+        let leaves = [leaf(0), leaf(1), leaf(2), leaf(3)];
+        let left = parent(leaves[0], leaves[1]);
+        let right = parent(leaves[2], leaves[3]);
+        let proofs = [
+            vec![leaves[1], right],
+            vec![leaves[0], right],
+            vec![leaves[3], left],
+            vec![leaves[2], left],
+        ];
+        (parent(left, right), proofs)
+    }
+
+    #[test]
+    fn accepts_a_valid_proof_for_every_leaf() {
+        let (root, proofs) = tree();
+
+        for (n, proof) in proofs.iter().enumerate() {
+            assert!(verify_merkle_proof(proof, root, leaf(n as u8)));
+        }
+    }
+
+    #[test]
+    fn rejects_a_wrong_leaf() {
+        let (root, proofs) = tree();
+
+        assert!(!verify_merkle_proof(&proofs[0], root, leaf(1)));
+        assert!(!verify_merkle_proof(&proofs[0], root, leaf(4)));
+    }
+
+    #[test]
+    fn empty_proof_matches_only_a_single_leaf_root() {
+        let root = leaf(0);
+
+        assert!(verify_merkle_proof(&[], root, leaf(0)));
+        assert!(!verify_merkle_proof(&[], root, leaf(1)));
+        assert!(!verify_merkle_proof(&[], tree().0, leaf(0)));
+    }
+}
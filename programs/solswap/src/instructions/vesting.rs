@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{
+    check_offer_authority, error::ErrorCode, Delegate, Offer, VestingEscrow, VestingParams,
+    DELEGATE_AMEND,
+};
+
+#[derive(Accounts)]
+pub struct SetOfferVesting<'info> {
+    /// Depositor, or a manager holding a delegation from them
+    pub authority: Signer<'info>,
+
+    pub depositor: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"delegate", depositor.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    #[account(
+        mut,
+        has_one = depositor,
+        seeds = [offer.token_mint_in.as_ref(), depositor.key().as_ref()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+/// Sets or clears the schedule on which takers of the offer receive their tokens.
+pub fn process_set_offer_vesting(
+    ctx: Context<SetOfferVesting>,
+    vesting: Option<VestingParams>,
+) -> Result<()> {
+    // The depositor could otherwise block fills of a position they sold
+    require!(
+        ctx.accounts.offer.position_mint.is_none(),
+        ErrorCode::OfferTokenized
+    );
+    check_offer_authority(
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.accounts.delegate.as_deref(),
+        DELEGATE_AMEND,
+    )?;
+
+    if let Some(params) = vesting {
+        params.validate()?;
+        require!(
+            ctx.accounts.offer.barter_amount_out.is_none(),
+            ErrorCode::VestingNotSupported
+        );
+    }
+
+    ctx.accounts.offer.vesting = vesting;
+    ctx.accounts.offer.updated_slot = Clock::get()?.slot;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        has_one = mint,
+        seeds = [b"vesting", vesting_escrow.offer.as_ref(), beneficiary.key().as_ref()],
+        bump = vesting_escrow.bump
+    )]
+    pub vesting_escrow: Account<'info, VestingEscrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting_escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Sends everything vested so far to the beneficiary, closing the escrow
+/// once it is fully claimed.
+pub fn process_claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+    let escrow = &ctx.accounts.vesting_escrow;
+    let amount = escrow.claimable(Clock::get()?.unix_timestamp);
+    require!(amount > 0, ErrorCode::NothingVested);
+
+    let offer = escrow.offer;
+    let beneficiary = escrow.beneficiary;
+    let escrow_seeds = &[
+        b"vesting".as_ref(),
+        offer.as_ref(),
+        beneficiary.as_ref(),
+        &[escrow.bump],
+    ];
+    let signer_seeds = &[&escrow_seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.vesting_vault.to_account_info(),
+        to: ctx.accounts.beneficiary_token_account.to_account_info(),
+        authority: escrow.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+    let escrow = &mut ctx.accounts.vesting_escrow;
+    escrow.claimed += amount;
+    msg!("Claimed {} of {}", escrow.claimed, escrow.total);
+
+    if escrow.claimed == escrow.total {
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.vesting_vault.to_account_info(),
+            destination: ctx.accounts.beneficiary.to_account_info(),
+            authority: escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        close_account(cpi_ctx)?;
+        escrow.close(ctx.accounts.beneficiary.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -1,11 +1,13 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -20,11 +22,239 @@ pub mod solswap {
         crate::instructions::process_init_config(ctx, usdc_mint)
     }
 
-    pub fn create_offer(ctx: Context<CreateOffer>, amount: u64) -> Result<()> {
-        crate::instructions::process_create_offer(ctx, amount)
+    pub fn update_config(ctx: Context<UpdateConfig>, max_referral_bps: u16) -> Result<()> {
+        crate::instructions::process_update_config(ctx, max_referral_bps)
     }
 
-    pub fn take_offer(ctx: Context<TakeOffer>) -> Result<()> {
-        crate::instructions::process_take_offer(ctx)
+    pub fn set_circuit_breaker(
+        ctx: Context<UpdateConfig>,
+        circuit_breaker: CircuitBreaker,
+    ) -> Result<()> {
+        crate::instructions::process_set_circuit_breaker(ctx, circuit_breaker)
+    }
+
+    pub fn set_fee_tiers(ctx: Context<UpdateConfig>, fee_tiers: Vec<FeeTier>) -> Result<()> {
+        crate::instructions::process_set_fee_tiers(ctx, fee_tiers)
+    }
+
+    pub fn set_price_feed(ctx: Context<SetPriceFeed>, feed_id: [u8; 32]) -> Result<()> {
+        crate::instructions::process_set_price_feed(ctx, feed_id)
+    }
+
+    pub fn set_mint_limits(
+        ctx: Context<SetMintLimits>,
+        min_amount: u64,
+        max_amount: u64,
+    ) -> Result<()> {
+        crate::instructions::process_set_mint_limits(ctx, min_amount, max_amount)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        crate::instructions::process_migrate_config(ctx)
+    }
+
+    pub fn migrate_offer(ctx: Context<MigrateOffer>) -> Result<()> {
+        crate::instructions::process_migrate_offer(ctx)
+    }
+
+    pub fn create_offer(
+        ctx: Context<CreateOffer>,
+        amount: u64,
+        auction: Option<AuctionParams>,
+        trigger: Option<TriggerParams>,
+        dca: Option<DcaParams>,
+        client_order_id: u128,
+        memo: Option<String>,
+    ) -> Result<()> {
+        crate::instructions::process_create_offer(
+            ctx,
+            amount,
+            auction,
+            trigger,
+            dca,
+            client_order_id,
+            memo,
+            None,
+        )
+    }
+
+    pub fn create_barter_offer(
+        ctx: Context<CreateOffer>,
+        amount: u64,
+        amount_out: u64,
+        client_order_id: u128,
+        memo: Option<String>,
+    ) -> Result<()> {
+        crate::instructions::process_create_offer(
+            ctx,
+            amount,
+            None,
+            None,
+            None,
+            client_order_id,
+            memo,
+            Some(amount_out),
+        )
+    }
+
+    pub fn set_offer_vesting(
+        ctx: Context<SetOfferVesting>,
+        vesting: Option<VestingParams>,
+    ) -> Result<()> {
+        crate::instructions::process_set_offer_vesting(ctx, vesting)
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        crate::instructions::process_claim_vested(ctx)
+    }
+
+    pub fn set_taker_allowlist(
+        ctx: Context<SetTakerAllowlist>,
+        root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        crate::instructions::process_set_taker_allowlist(ctx, root)
+    }
+
+    pub fn set_taker_gate(ctx: Context<SetTakerAllowlist>, gate: Option<TokenGate>) -> Result<()> {
+        crate::instructions::process_set_taker_gate(ctx, gate)
+    }
+
+    pub fn set_offer_grid(ctx: Context<SetOfferGrid>, grid: Option<Grid>) -> Result<()> {
+        crate::instructions::process_set_offer_grid(ctx, grid)
+    }
+
+    pub fn cancel_grid(ctx: Context<CancelGrid>) -> Result<()> {
+        crate::instructions::process_cancel_grid(ctx)
+    }
+
+    pub fn mint_offer_position(ctx: Context<MintOfferPosition>) -> Result<()> {
+        crate::instructions::process_mint_offer_position(ctx)
+    }
+
+    pub fn amend_offer(ctx: Context<AmendOffer>, new_amount: u64) -> Result<()> {
+        crate::instructions::process_amend_offer(ctx, new_amount)
+    }
+
+    pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+        crate::instructions::process_cancel_offer(ctx)
+    }
+
+    pub fn set_delegate(ctx: Context<SetDelegate>, permissions: u8, expiry: i64) -> Result<()> {
+        crate::instructions::process_set_delegate(ctx, permissions, expiry)
+    }
+
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        crate::instructions::process_revoke_delegate(ctx)
+    }
+
+    pub fn take_offer(
+        ctx: Context<TakeOffer>,
+        referral_bps: u16,
+        proof: Vec<[u8; 32]>,
+        expected_vesting: Option<VestingParams>,
+    ) -> Result<()> {
+        crate::instructions::process_take_offer(ctx, referral_bps, proof, expected_vesting)
+    }
+
+    pub fn create_basket_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateBasketOffer<'info>>,
+        basket_id: u64,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        crate::instructions::process_create_basket_offer(ctx, basket_id, amounts)
+    }
+
+    pub fn take_basket_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeBasketOffer<'info>>,
+        max_amount_out: u64,
+    ) -> Result<()> {
+        crate::instructions::process_take_basket_offer(ctx, max_amount_out)
+    }
+
+    pub fn cancel_basket_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelBasketOffer<'info>>,
+    ) -> Result<()> {
+        crate::instructions::process_cancel_basket_offer(ctx)
+    }
+
+    pub fn create_ladder(
+        ctx: Context<CreateLadder>,
+        ladder_id: u64,
+        amount: u64,
+        params: LadderParams,
+    ) -> Result<()> {
+        crate::instructions::process_create_ladder(ctx, ladder_id, amount, params)
+    }
+
+    pub fn take_ladder_level(ctx: Context<TakeLadderLevel>, level: u8, amount: u64) -> Result<()> {
+        crate::instructions::process_take_ladder_level(ctx, level, amount)
+    }
+
+    pub fn cancel_ladder(ctx: Context<CancelLadder>) -> Result<()> {
+        crate::instructions::process_cancel_ladder(ctx)
+    }
+
+    pub fn take_barter(
+        ctx: Context<TakeBarter>,
+        expected_amount_in: u64,
+        max_amount_out: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        crate::instructions::process_take_barter(ctx, expected_amount_in, max_amount_out, proof)
+    }
+
+    pub fn commit_take(ctx: Context<CommitTake>, hash: [u8; 32], bond: u64) -> Result<()> {
+        crate::instructions::process_commit_take(ctx, hash, bond)
+    }
+
+    pub fn reveal_take(
+        ctx: Context<RevealTake>,
+        amount: u64,
+        max_price: u64,
+        salt: [u8; 32],
+        referral_bps: u16,
+        proof: Vec<[u8; 32]>,
+        expected_vesting: Option<VestingParams>,
+    ) -> Result<()> {
+        crate::instructions::process_reveal_take(
+            ctx,
+            amount,
+            max_price,
+            salt,
+            referral_bps,
+            proof,
+            expected_vesting,
+        )
+    }
+
+    pub fn forfeit_commitment(ctx: Context<ForfeitCommitment>) -> Result<()> {
+        crate::instructions::process_forfeit_commitment(ctx)
+    }
+
+    pub fn quote_take(ctx: Context<QuoteTake>, amount: u64, referral_bps: u16) -> Result<Quote> {
+        crate::instructions::process_quote_take(ctx, amount, referral_bps)
+    }
+
+    pub fn execute_trigger(ctx: Context<ExecuteTrigger>) -> Result<()> {
+        crate::instructions::process_execute_trigger(ctx)
+    }
+
+    pub fn flash_take_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashTakeOffer<'info>>,
+        callback_data: Vec<u8>,
+    ) -> Result<()> {
+        crate::instructions::process_flash_take_offer(ctx, callback_data)
+    }
+
+    pub fn take_offers_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeOffersBatch<'info>>,
+        max_amount_in: u64,
+        max_price: u64,
+    ) -> Result<()> {
+        crate::instructions::process_take_offers_batch(ctx, max_amount_in, max_price)
+    }
+
+    pub fn fill_quote(ctx: Context<FillQuote>, quote: SignedQuote) -> Result<()> {
+        crate::instructions::process_fill_quote(ctx, quote)
     }
 }
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_BASKET_LEGS: usize = 4;
+
+/// One escrowed mint of a basket; its vault is the basket PDA's ATA for `mint`.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct BasketLeg {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+}
+
+/// An offer escrowing several mints at once, filled atomically for a single
+/// counter-asset worth the oracle value of all legs.
+#[account]
+#[derive(InitSpace)]
+pub struct BasketOffer {
+    pub depositor: Pubkey,
+    pub basket_id: u64,
+    pub token_mint_out: Pubkey,
+    /// Only the first `leg_count` legs are used
+    pub legs: [BasketLeg; MAX_BASKET_LEGS],
+    pub leg_count: u8,
+    pub bump: u8,
+}
+
+impl BasketOffer {
+    pub fn legs(&self) -> &[BasketLeg] {
+        &self.legs[..self.leg_count as usize]
+    }
+}
+
+/// Admin-registered Pyth feed pricing `mint` in USD, used to value basket legs.
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub mint: Pubkey,
+    pub feed_id: [u8; 32],
+    pub bump: u8,
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::CircuitBreaker;
+
+pub const PAIR_BREAKER_VERSION: u8 = 1;
+pub const PAIR_BREAKER_RESERVED_SPACE: usize = 64;
+
+/// Circuit breaker state shared by both directions of a pair, stored under
+/// the pair's mints in ascending order.
+#[account]
+#[derive(InitSpace)]
+pub struct PairBreaker {
+    pub version: u8,
+    /// Lower of the pair's two mints
+    pub mint_a: Pubkey,
+    /// Higher of the pair's two mints
+    pub mint_b: Pubkey,
+    /// Oracle SOL/USD price at the last guarded fill, in the oracle's units
+    pub last_price: i64,
+    pub last_price_timestamp: i64,
+    /// Start of the current notional window
+    pub window_start: i64,
+    /// USDC notional filled since `window_start`
+    pub window_notional: u64,
+    pub bump: u8,
+    /// Zeroed space new fields are carved from without reallocating
+    pub reserved: [u8; PAIR_BREAKER_RESERVED_SPACE],
+}
+
+impl PairBreaker {
+    /// Loads the breaker PDA if the pair has been filled before; a pair without one has no history.
+    pub fn load(info: &AccountInfo) -> Result<Option<PairBreaker>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Some(PairBreaker::try_deserialize(&mut &data[..])?))
+    }
+
+    /// Sets the pair on a freshly created breaker account.
+    pub fn init_if_empty(&mut self, mint_x: Pubkey, mint_y: Pubkey, bump: u8) {
+        if self.version == 0 {
+            self.version = PAIR_BREAKER_VERSION;
+            self.mint_a = mint_x.min(mint_y);
+            self.mint_b = mint_x.max(mint_y);
+            self.bump = bump;
+        }
+    }
+
+    /// Rejects a fill of `notional` USDC at `price` that would trip `breaker`.
+    pub fn check_fill(
+        &self,
+        breaker: &CircuitBreaker,
+        price: i64,
+        notional: u64,
+        now: i64,
+    ) -> Result<()> {
+        breaker.check_price(price, self.last_price, self.last_price_timestamp, now)?;
+        breaker.check_notional(self.notional_in_window(breaker, now), notional)
+    }
+
+    /// Applies the breaker to a fill and books it as the pair's reference.
+    pub fn guard_fill(
+        &mut self,
+        breaker: &CircuitBreaker,
+        price: i64,
+        notional: u64,
+        now: i64,
+    ) -> Result<()> {
+        breaker.check_price(price, self.last_price, self.last_price_timestamp, now)?;
+        self.guard_notional(breaker, notional, now)?;
+        self.last_price = price;
+        self.last_price_timestamp = now;
+        Ok(())
+    }
+
+    /// Applies only the notional cap and books the fill in the current
+    /// window; for fills at a fixed price rather than the oracle's.
+    pub fn guard_notional(
+        &mut self,
+        breaker: &CircuitBreaker,
+        notional: u64,
+        now: i64,
+    ) -> Result<()> {
+        if now - self.window_start >= breaker.notional_window {
+            self.window_start = now;
+            self.window_notional = 0;
+        }
+        breaker.check_notional(self.window_notional, notional)?;
+        self.window_notional = self.window_notional.saturating_add(notional);
+        Ok(())
+    }
+
+    fn notional_in_window(&self, breaker: &CircuitBreaker, now: i64) -> u64 {
+        if now - self.window_start >= breaker.notional_window {
+            0
+        } else {
+            self.window_notional
+        }
+    }
+}
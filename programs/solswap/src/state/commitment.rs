@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{Offer, REVEAL_DELAY_SLOTS, REVEAL_WINDOW_SLOTS};
+
+/// A taker's hidden intent to fill `offer`, revealed a few slots later.
+///
+/// `bond` lamports are held on top of rent and go back to the taker on reveal,
+/// or to the depositor if the commitment is never revealed while the offer
+/// stays as it was committed to.
+#[account]
+#[derive(InitSpace)]
+pub struct TakeCommitment {
+    pub taker: Pubkey,
+    pub offer: Pubkey,
+    pub depositor: Pubkey,
+    /// `updated_slot` of the offer when the commitment was made
+    pub offer_updated_slot: u64,
+    pub hash: [u8; 32],
+    pub bond: u64,
+    pub commit_slot: u64,
+    pub bump: u8,
+}
+
+impl TakeCommitment {
+    /// Hash the taker commits to; computed the same way off-chain.
+    pub fn hash_take(offer: &Pubkey, amount: u64, max_price: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[
+            offer.as_ref(),
+            &amount.to_le_bytes(),
+            &max_price.to_le_bytes(),
+            salt,
+        ])
+        .to_bytes()
+    }
+
+    pub fn reveal_starts_at(&self) -> u64 {
+        self.commit_slot + REVEAL_DELAY_SLOTS
+    }
+
+    /// Last slot the commitment can be revealed in; after it the bond is forfeited.
+    pub fn reveal_ends_at(&self) -> u64 {
+        self.reveal_starts_at() + REVEAL_WINDOW_SLOTS
+    }
+
+    /// Whether `offer` is still the one committed to, with the same terms.
+    /// Otherwise the depositor cancelled or changed it and the bond is not theirs.
+    pub fn offer_unchanged(&self, offer: &Offer) -> bool {
+        offer.depositor == self.depositor && offer.updated_slot == self.offer_updated_slot
+    }
+}
@@ -1,7 +1,244 @@
 use anchor_lang::prelude::*;
 
+use crate::{bps_of, error::ErrorCode, BPS_DENOMINATOR};
+
+pub const CONFIG_VERSION: u8 = 2;
+pub const CONFIG_RESERVED_SPACE: usize = 53;
+pub const MAX_FEE_TIERS: usize = 4;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
+    pub version: u8,
     pub usdc_mint: Pubkey,
+    pub admin: Pubkey,
+    pub max_referral_bps: u16,
+    pub circuit_breaker: CircuitBreaker,
+    /// Volume tiers in ascending `min_volume`; only the first `fee_tier_count` are used
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    pub fee_tier_count: u8,
+    /// Zeroed space new fields are carved from without reallocating
+    pub reserved: [u8; CONFIG_RESERVED_SPACE],
+}
+
+/// Per-pair limits on oracle-priced fills. A zero threshold disables that check.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct CircuitBreaker {
+    /// Largest allowed move of the oracle price against the pair's last fill price
+    pub max_price_move_bps: u16,
+    /// Seconds after a fill during which its price is used as the reference
+    pub price_window: i64,
+    /// Largest USDC notional the pair may fill per window
+    pub max_window_notional: u64,
+    /// Length of a notional window in seconds
+    pub notional_window: i64,
+}
+
+impl CircuitBreaker {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.price_window >= 0 && self.notional_window >= 0,
+            ErrorCode::InvalidCircuitBreaker
+        );
+        require!(
+            self.max_price_move_bps == 0 || self.price_window > 0,
+            ErrorCode::InvalidCircuitBreaker
+        );
+        require!(
+            self.max_window_notional == 0 || self.notional_window > 0,
+            ErrorCode::InvalidCircuitBreaker
+        );
+        Ok(())
+    }
+
+    /// Rejects `price` if it moved too far from `last_price`, set at `last_time`.
+    pub fn check_price(&self, price: i64, last_price: i64, last_time: i64, now: i64) -> Result<()> {
+        if self.max_price_move_bps == 0 || last_price <= 0 || now - last_time > self.price_window {
+            return Ok(());
+        }
+
+        let moved = (price as i128 - last_price as i128).unsigned_abs();
+        require!(
+            moved * BPS_DENOMINATOR as u128 <= self.max_price_move_bps as u128 * last_price as u128,
+            ErrorCode::CircuitBreakerPriceMove
+        );
+        Ok(())
+    }
+
+    /// Rejects `notional` if it would take the window's `used` notional past the cap.
+    pub fn check_notional(&self, used: u64, notional: u64) -> Result<()> {
+        require!(
+            self.max_window_notional == 0
+                || used.saturating_add(notional) <= self.max_window_notional,
+            ErrorCode::CircuitBreakerNotional
+        );
+        Ok(())
+    }
+}
+
+/// Fee rates for wallets whose 30-day USDC volume is at least `min_volume`.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct FeeTier {
+    pub min_volume: u64,
+    /// Charged to the taker in `token_mint_out`, on top of the price
+    pub taker_fee_bps: u16,
+    /// Paid to the depositor out of the taker fee
+    pub maker_rebate_bps: u16,
+}
+
+/// Fee rates that apply to one fill.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeRates {
+    pub taker_fee_bps: u16,
+    pub maker_rebate_bps: u16,
+}
+
+impl FeeRates {
+    /// Taker fee on a payment of `amount_out` and the maker's rebate out of it.
+    pub fn fees_on(&self, amount_out: u64) -> (u64, u64) {
+        let taker_fee = bps_of(amount_out, self.taker_fee_bps);
+        (
+            taker_fee,
+            bps_of(amount_out, self.maker_rebate_bps).min(taker_fee),
+        )
+    }
+}
+
+impl Config {
+    /// Highest tier `volume` qualifies for; wallets below every tier pay nothing.
+    pub fn fee_tier(&self, volume: u64) -> FeeTier {
+        self.fee_tiers[..self.fee_tier_count as usize]
+            .iter()
+            .rev()
+            .find(|tier| volume >= tier.min_volume)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The taker pays their own tier's fee and the maker earns their tier's rebate.
+    pub fn fee_rates(&self, taker_volume: u64, maker_volume: u64) -> FeeRates {
+        FeeRates {
+            taker_fee_bps: self.fee_tier(taker_volume).taker_fee_bps,
+            maker_rebate_bps: self.fee_tier(maker_volume).maker_rebate_bps,
+        }
+    }
+}
+
+/// Checks a tier table is ascending, within 100% and never rebates more than it charges.
+pub fn validate_fee_tiers(tiers: &[FeeTier]) -> Result<()> {
+    require!(tiers.len() <= MAX_FEE_TIERS, ErrorCode::InvalidFeeTiers);
+    for (i, tier) in tiers.iter().enumerate() {
+        require!(
+            (tier.taker_fee_bps as u64) < BPS_DENOMINATOR
+                && tier.maker_rebate_bps <= tier.taker_fee_bps,
+            ErrorCode::InvalidFeeTiers
+        );
+        if i > 0 {
+            require!(
+                tier.min_volume > tiers[i - 1].min_volume,
+                ErrorCode::InvalidFeeTiers
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(min_volume: u64, taker_fee_bps: u16, maker_rebate_bps: u16) -> FeeTier {
+        FeeTier {
+            min_volume,
+            taker_fee_bps,
+            maker_rebate_bps,
+        }
+    }
+
+    fn config(tiers: &[FeeTier]) -> Config {
+        let mut fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        fee_tiers[..tiers.len()].copy_from_slice(tiers);
+        Config {
+            version: 0,
+            usdc_mint: Pubkey::new_unique(),
+            admin: Pubkey::new_unique(),
+            max_referral_bps: 0,
+            circuit_breaker: CircuitBreaker::default(),
+            fee_tiers,
+            fee_tier_count: tiers.len() as u8,
+            reserved: [0; CONFIG_RESERVED_SPACE],
+        }
+    }
+
+    fn tiers() -> [FeeTier; 3] {
+        [tier(0, 30, 0), tier(1_000, 20, 5), tier(10_000, 10, 10)]
+    }
+
+    #[test]
+    fn picks_the_highest_tier_reached() {
+        let config = config(&tiers());
+
+        assert_eq!(config.fee_tier(0), tier(0, 30, 0));
+        assert_eq!(config.fee_tier(999), tier(0, 30, 0));
+        assert_eq!(config.fee_tier(1_000), tier(1_000, 20, 5));
+        assert_eq!(config.fee_tier(u64::MAX), tier(10_000, 10, 10));
+    }
+
+    #[test]
+    fn ignores_tiers_past_the_count() {
+        let mut config = config(&tiers());
+        config.fee_tier_count = 1;
+        assert_eq!(config.fee_tier(u64::MAX), tier(0, 30, 0));
+
+        config.fee_tier_count = 0;
+        assert_eq!(config.fee_tier(u64::MAX), FeeTier::default());
+    }
+
+    #[test]
+    fn fees_below_the_first_tier_are_free() {
+        let config = config(&[tier(1_000, 20, 5)]);
+
+        assert_eq!(config.fee_tier(999), FeeTier::default());
+    }
+
+    #[test]
+    fn taker_and_maker_use_their_own_tiers() {
+        let config = config(&tiers());
+
+        assert_eq!(
+            config.fee_rates(0, 10_000),
+            FeeRates {
+                taker_fee_bps: 30,
+                maker_rebate_bps: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn accepts_an_ascending_table() {
+        assert!(validate_fee_tiers(&tiers()).is_ok());
+        assert!(validate_fee_tiers(&[]).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_tables() {
+        let invalid = [
+            vec![tier(0, 30, 0), tier(0, 20, 0)],
+            vec![tier(1_000, 30, 0), tier(0, 20, 0)],
+            vec![tier(0, 10, 11)],
+            vec![tier(0, BPS_DENOMINATOR as u16, 0)],
+            vec![tier(0, 30, 0); MAX_FEE_TIERS + 1],
+        ];
+
+        for tiers in invalid {
+            assert_eq!(
+                validate_fee_tiers(&tiers).unwrap_err(),
+                ErrorCode::InvalidFeeTiers.into()
+            );
+        }
+    }
 }
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+pub const DELEGATE_CREATE: u8 = 1 << 0;
+pub const DELEGATE_AMEND: u8 = 1 << 1;
+pub const DELEGATE_CANCEL: u8 = 1 << 2;
+
+/// Lets `manager` create, amend or cancel offers on behalf of `depositor`.
+///
+/// Deposits made by a manager are pulled from the depositor's token account
+/// through an SPL approval granted to this PDA.
+#[account]
+#[derive(InitSpace)]
+pub struct Delegate {
+    pub depositor: Pubkey,
+    pub manager: Pubkey,
+    pub permissions: u8,
+    pub expiry: i64,
+    pub bump: u8,
+}
+
+impl Delegate {
+    pub fn allows(&self, permission: u8, now: i64) -> bool {
+        self.permissions & permission == permission && now < self.expiry
+    }
+}
+
+/// Checks that `authority` is the depositor or a manager holding an unexpired
+/// delegation with `permission`.
+pub fn check_offer_authority(
+    authority: &Pubkey,
+    depositor: &Pubkey,
+    delegate: Option<&Delegate>,
+    permission: u8,
+) -> Result<()> {
+    if authority == depositor {
+        return Ok(());
+    }
+
+    let delegate = delegate.ok_or(ErrorCode::Unauthorized)?;
+    require_keys_eq!(delegate.manager, *authority, ErrorCode::Unauthorized);
+    require_keys_eq!(delegate.depositor, *depositor, ErrorCode::Unauthorized);
+    require!(
+        delegate.allows(permission, Clock::get()?.unix_timestamp),
+        ErrorCode::DelegateNotPermitted
+    );
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, lamports_to_usdc, usdc_to_lamports, WSOL_MINT};
+
+pub const MAX_LADDER_LEVELS: usize = 8;
+
+/// How a ladder's deposit is split across its levels.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum SizeDistribution {
+    Flat,
+    /// Level `i` gets a share proportional to `i + 1`, so size grows away from the start
+    Increasing,
+    /// Level `i` gets a share proportional to `levels - i`
+    Decreasing,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct LadderParams {
+    /// SOL/USD price of the first level, in the oracle's units
+    pub start_price: i64,
+    /// Price gap between levels; sell ladders step up, buy ladders step down
+    pub price_step: i64,
+    pub levels: u8,
+    pub distribution: SizeDistribution,
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct LadderLevel {
+    pub price: i64,
+    /// Amount of `token_mint_in` left at this level
+    pub amount: u64,
+}
+
+impl LadderParams {
+    /// Splits `amount` across the levels; rounding dust goes to the last one.
+    /// `sells_sol` picks the direction prices move in from `start_price`.
+    pub fn levels(&self, amount: u64, sells_sol: bool) -> Result<[LadderLevel; MAX_LADDER_LEVELS]> {
+        let count = self.levels as usize;
+        require!(
+            (1..=MAX_LADDER_LEVELS).contains(&count) && self.start_price > 0 && self.price_step > 0,
+            ErrorCode::InvalidLadder
+        );
+
+        let weight = |i: usize| -> u128 {
+            match self.distribution {
+                SizeDistribution::Flat => 1,
+                SizeDistribution::Increasing => i as u128 + 1,
+                SizeDistribution::Decreasing => (count - i) as u128,
+            }
+        };
+        let total_weight: u128 = (0..count).map(weight).sum();
+
+        let mut levels = [LadderLevel::default(); MAX_LADDER_LEVELS];
+        let mut allocated = 0;
+        for (i, level) in levels.iter_mut().take(count).enumerate() {
+            let offset = self
+                .price_step
+                .checked_mul(i as i64)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let price = if sells_sol {
+                self.start_price.checked_add(offset)
+            } else {
+                self.start_price.checked_sub(offset)
+            }
+            .ok_or(ErrorCode::MathOverflow)?;
+
+            let size = if i + 1 == count {
+                amount - allocated
+            } else {
+                (amount as u128 * weight(i) / total_weight) as u64
+            };
+            require!(price > 0 && size > 0, ErrorCode::InvalidLadder);
+
+            allocated += size;
+            *level = LadderLevel {
+                price,
+                amount: size,
+            };
+        }
+        Ok(levels)
+    }
+}
+
+/// Several fixed-price levels selling one deposit, escrowed in a single
+/// vault (the ladder PDA's ATA) and cancelled together.
+#[account]
+#[derive(InitSpace)]
+pub struct Ladder {
+    pub depositor: Pubkey,
+    pub ladder_id: u64,
+    pub token_mint_in: Pubkey,
+    pub token_mint_out: Pubkey,
+    pub vault: Pubkey,
+    /// Only the first `level_count` levels are used
+    pub levels: [LadderLevel; MAX_LADDER_LEVELS],
+    pub level_count: u8,
+    pub bump: u8,
+}
+
+impl Ladder {
+    pub fn levels(&self) -> &[LadderLevel] {
+        &self.levels[..self.level_count as usize]
+    }
+
+    pub fn remaining_amount(&self) -> u64 {
+        self.levels().iter().map(|level| level.amount).sum()
+    }
+
+    /// Amount of `token_mint_out` owed for `amount` of `token_mint_in` at `price`.
+    pub fn amount_out_for(&self, amount: u64, price: i64) -> u64 {
+        if self.token_mint_in == WSOL_MINT {
+            lamports_to_usdc(amount, price)
+        } else {
+            usdc_to_lamports(amount, price)
+        }
+    }
+
+    /// USDC side of a fill, metered by the pair's circuit breaker.
+    pub fn usdc_notional(&self, amount_in: u64, amount_out: u64) -> u64 {
+        if self.token_mint_in == WSOL_MINT {
+            amount_out
+        } else {
+            amount_in
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(levels: u8, distribution: SizeDistribution) -> LadderParams {
+        LadderParams {
+            start_price: 100,
+            price_step: 10,
+            levels,
+            distribution,
+        }
+    }
+
+    fn sizes(levels: &[LadderLevel; MAX_LADDER_LEVELS]) -> Vec<u64> {
+        levels.iter().map(|level| level.amount).collect()
+    }
+
+    #[test]
+    fn flat_splits_evenly_with_dust_on_the_last_level() {
+        let levels = params(3, SizeDistribution::Flat).levels(100, true).unwrap();
+
+        assert_eq!(sizes(&levels), [33, 33, 34, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn increasing_weights_by_level() {
+        // Weights 1, 2, 3, 4 out of 10
+        let levels = params(4, SizeDistribution::Increasing)
+            .levels(1_003, true)
+            .unwrap();
+
+        assert_eq!(sizes(&levels), [100, 200, 300, 403, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn decreasing_weights_by_level() {
+        // Weights 4, 3, 2, 1 out of 10
+        let levels = params(4, SizeDistribution::Decreasing)
+            .levels(1_003, true)
+            .unwrap();
+
+        assert_eq!(sizes(&levels), [401, 300, 200, 102, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn allocates_the_whole_amount() {
+        for distribution in [
+            SizeDistribution::Flat,
+            SizeDistribution::Increasing,
+            SizeDistribution::Decreasing,
+        ] {
+            let levels = params(7, distribution).levels(999_999, false).unwrap();
+            assert_eq!(
+                levels.iter().map(|level| level.amount).sum::<u64>(),
+                999_999
+            );
+        }
+    }
+
+    #[test]
+    fn sell_ladders_step_up_and_buy_ladders_step_down() {
+        let sells = params(3, SizeDistribution::Flat).levels(30, true).unwrap();
+        let buys = params(3, SizeDistribution::Flat).levels(30, false).unwrap();
+
+        let prices =
+            |levels: &[LadderLevel]| levels[..3].iter().map(|l| l.price).collect::<Vec<_>>();
+        assert_eq!(prices(&sells), [100, 110, 120]);
+        assert_eq!(prices(&buys), [100, 90, 80]);
+    }
+
+    #[test]
+    fn rejects_invalid_level_counts() {
+        let flat = SizeDistribution::Flat;
+        let invalid = ErrorCode::InvalidLadder.into();
+
+        assert_eq!(params(0, flat).levels(100, true).unwrap_err(), invalid);
+        assert_eq!(
+            params(MAX_LADDER_LEVELS as u8 + 1, flat)
+                .levels(100, true)
+                .unwrap_err(),
+            invalid
+        );
+    }
+
+    #[test]
+    fn rejects_empty_levels() {
+        // Level 0 rounds down to nothing
+        let result = params(3, SizeDistribution::Increasing).levels(5, true);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidLadder.into());
+    }
+
+    #[test]
+    fn rejects_non_positive_prices() {
+        let mut zero_start = params(2, SizeDistribution::Flat);
+        zero_start.start_price = 0;
+        let mut zero_step = params(2, SizeDistribution::Flat);
+        zero_step.price_step = 0;
+        // A buy ladder stepping from 100 down through zero by its sixth level
+        let mut steep = params(6, SizeDistribution::Flat);
+        steep.price_step = 20;
+        let invalid: Error = ErrorCode::InvalidLadder.into();
+
+        assert_eq!(zero_start.levels(100, true).unwrap_err(), invalid);
+        assert_eq!(zero_step.levels(100, true).unwrap_err(), invalid);
+        assert!(steep.levels(100, true).is_ok());
+        assert_eq!(steep.levels(100, false).unwrap_err(), invalid);
+    }
+}
@@ -0,0 +1,270 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, Auction, CircuitBreaker, Config, Dca, FeeTier, Offer, Trigger,
+    CONFIG_RESERVED_SPACE, CONFIG_VERSION, MAX_FEE_TIERS, OFFER_RESERVED_SPACE, OFFER_VERSION,
+};
+
+/// Offer fields before v3. The auction, trigger and DCA settings are absent
+/// from offers created before those features; v2 prefixed the same fields
+/// with a version byte and followed them with reserved space.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferV1 {
+    pub token_mint_in: Pubkey,
+    pub token_mint_out: Pubkey,
+    pub deposited_amount: u64,
+    pub depositor: Pubkey,
+    pub vault: Pubkey,
+    pub bump: u8,
+    pub auction: Option<Auction>,
+    pub trigger: Option<Trigger>,
+    pub dca: Option<Dca>,
+}
+
+/// Config layout before accounts carried a version byte. Configs created
+/// before referral fees end after `usdc_mint` and have no admin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigV1 {
+    pub usdc_mint: Pubkey,
+    pub admin: Pubkey,
+    pub max_referral_bps: u16,
+}
+
+/// Reads a trailing field, treating missing bytes as an older, shorter layout.
+fn read_trailing<T: AnchorDeserialize + Default>(data: &mut &[u8]) -> Result<T> {
+    if data.is_empty() {
+        return Ok(T::default());
+    }
+    Ok(T::deserialize(data)?)
+}
+
+impl OfferV1 {
+    /// Decodes the account data after the discriminator.
+    pub fn decode(mut data: &[u8]) -> Result<Self> {
+        let data = &mut data;
+        Ok(OfferV1 {
+            token_mint_in: Pubkey::deserialize(data)?,
+            token_mint_out: Pubkey::deserialize(data)?,
+            deposited_amount: u64::deserialize(data)?,
+            depositor: Pubkey::deserialize(data)?,
+            vault: Pubkey::deserialize(data)?,
+            bump: u8::deserialize(data)?,
+            auction: read_trailing(data)?,
+            trigger: read_trailing(data)?,
+            dca: read_trailing(data)?,
+        })
+    }
+
+    /// Decodes v2 account data after the discriminator.
+    pub fn decode_v2(data: &[u8]) -> Result<Self> {
+        require!(data.first() == Some(&2), ErrorCode::InvalidLegacyAccount);
+        Self::decode(&data[1..])
+    }
+
+    pub fn upgrade(self) -> Offer {
+        Offer {
+            version: OFFER_VERSION,
+            token_mint_in: self.token_mint_in,
+            token_mint_out: self.token_mint_out,
+            deposited_amount: self.deposited_amount,
+            depositor: self.depositor,
+            vault: self.vault,
+            bump: self.bump,
+            client_order_id: 0,
+            auction: self.auction,
+            trigger: self.trigger,
+            dca: self.dca,
+            memo: String::new(),
+            barter_amount_out: None,
+            vesting: None,
+            position_mint: None,
+            proceeds_recipient: None,
+            grid: None,
+            taker_merkle_root: None,
+            taker_gate: None,
+            updated_slot: 0,
+            reserved: [0; OFFER_RESERVED_SPACE],
+        }
+    }
+}
+
+impl ConfigV1 {
+    /// Decodes the account data after the discriminator.
+    pub fn decode(mut data: &[u8]) -> Result<Self> {
+        let data = &mut data;
+        Ok(ConfigV1 {
+            usdc_mint: Pubkey::deserialize(data)?,
+            admin: read_trailing(data)?,
+            max_referral_bps: read_trailing(data)?,
+        })
+    }
+
+    pub fn upgrade(self, admin: Pubkey) -> Config {
+        Config {
+            version: CONFIG_VERSION,
+            usdc_mint: self.usdc_mint,
+            admin,
+            max_referral_bps: self.max_referral_bps,
+            circuit_breaker: CircuitBreaker::default(),
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            fee_tier_count: 0,
+            reserved: [0; CONFIG_RESERVED_SPACE],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AuctionPricing, TriggerDirection};
+
+    /// Account sizes of the current layouts; new fields must come out of the
+    /// reserved space so these never change.
+    const OFFER_SPACE: usize = 483;
+    const CONFIG_SPACE: usize = 195;
+
+    fn header(data: &mut Vec<u8>) {
+        for byte in 1..=2 {
+            Pubkey::new_from_array([byte; 32]).serialize(data).unwrap();
+        }
+        500u64.serialize(data).unwrap();
+        Pubkey::new_from_array([3; 32]).serialize(data).unwrap();
+        Pubkey::new_from_array([4; 32]).serialize(data).unwrap();
+        254u8.serialize(data).unwrap();
+    }
+
+    fn trigger() -> Trigger {
+        Trigger {
+            price: 150,
+            direction: TriggerDirection::Below,
+            bounty: 10,
+            armed: false,
+        }
+    }
+
+    #[test]
+    fn decodes_v1_offer_without_trailing_fields() {
+        let mut data = Vec::new();
+        header(&mut data);
+
+        let offer = OfferV1::decode(&data).unwrap();
+        assert_eq!(offer.token_mint_in, Pubkey::new_from_array([1; 32]));
+        assert_eq!(offer.token_mint_out, Pubkey::new_from_array([2; 32]));
+        assert_eq!(offer.deposited_amount, 500);
+        assert_eq!(offer.depositor, Pubkey::new_from_array([3; 32]));
+        assert_eq!(offer.vault, Pubkey::new_from_array([4; 32]));
+        assert_eq!(offer.bump, 254);
+        assert_eq!(
+            (offer.auction, offer.trigger, offer.dca),
+            (None, None, None)
+        );
+    }
+
+    #[test]
+    fn decodes_v1_offer_with_trailing_fields() {
+        let auction = Auction {
+            pricing: AuctionPricing::Absolute {
+                start_price: 200,
+                end_price: 100,
+            },
+            start_time: 1,
+            duration: 60,
+        };
+        let mut data = Vec::new();
+        header(&mut data);
+        Some(auction).serialize(&mut data).unwrap();
+        Some(trigger()).serialize(&mut data).unwrap();
+        None::<Dca>.serialize(&mut data).unwrap();
+
+        let offer = OfferV1::decode(&data).unwrap();
+        assert_eq!(offer.auction, Some(auction));
+        assert_eq!(offer.trigger, Some(trigger()));
+        assert_eq!(offer.dca, None);
+    }
+
+    #[test]
+    fn decodes_v2_offer() {
+        let mut data = vec![2];
+        header(&mut data);
+        None::<Auction>.serialize(&mut data).unwrap();
+        Some(trigger()).serialize(&mut data).unwrap();
+        None::<Dca>.serialize(&mut data).unwrap();
+        data.extend([0; 256]);
+
+        let offer = OfferV1::decode_v2(&data).unwrap();
+        assert_eq!(offer.deposited_amount, 500);
+        assert_eq!(offer.trigger, Some(trigger()));
+
+        data[0] = 3;
+        assert!(OfferV1::decode_v2(&data).is_err());
+    }
+
+    #[test]
+    fn upgraded_offer_round_trips() {
+        let mut data = Vec::new();
+        header(&mut data);
+        let legacy = OfferV1::decode(&data).unwrap();
+
+        let bytes = legacy.clone().upgrade().try_to_vec().unwrap();
+        let offer = Offer::deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(offer.version, OFFER_VERSION);
+        assert_eq!(offer.deposited_amount, legacy.deposited_amount);
+        assert_eq!(offer.depositor, legacy.depositor);
+        assert_eq!(offer.bump, legacy.bump);
+        assert_eq!(offer.client_order_id, 0);
+        assert!(offer.memo.is_empty());
+        assert_eq!(offer.try_to_vec().unwrap(), bytes);
+    }
+
+    #[test]
+    fn decodes_config_before_and_after_referrals() {
+        let usdc_mint = Pubkey::new_from_array([5; 32]);
+        let admin = Pubkey::new_from_array([6; 32]);
+
+        let config = ConfigV1::decode(usdc_mint.as_ref()).unwrap();
+        assert_eq!(config.usdc_mint, usdc_mint);
+        assert_eq!(config.admin, Pubkey::default());
+        assert_eq!(config.max_referral_bps, 0);
+
+        let mut data = Vec::new();
+        usdc_mint.serialize(&mut data).unwrap();
+        admin.serialize(&mut data).unwrap();
+        250u16.serialize(&mut data).unwrap();
+        let config = ConfigV1::decode(&data).unwrap();
+        assert_eq!(
+            config,
+            ConfigV1 {
+                usdc_mint,
+                admin,
+                max_referral_bps: 250,
+            }
+        );
+    }
+
+    #[test]
+    fn upgraded_config_round_trips() {
+        let usdc_mint = Pubkey::new_from_array([5; 32]);
+        let admin = Pubkey::new_from_array([6; 32]);
+        let legacy = ConfigV1 {
+            usdc_mint,
+            admin: Pubkey::default(),
+            max_referral_bps: 100,
+        };
+
+        let bytes = legacy.upgrade(admin).try_to_vec().unwrap();
+        assert_eq!(bytes.len(), CONFIG_SPACE);
+        let config = Config::deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.usdc_mint, usdc_mint);
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.max_referral_bps, 100);
+        assert_eq!(config.fee_tier_count, 0);
+        assert_eq!(config.try_to_vec().unwrap(), bytes);
+    }
+
+    #[test]
+    fn reserved_space_keeps_account_sizes() {
+        assert_eq!(Offer::INIT_SPACE, OFFER_SPACE);
+        assert_eq!(Config::INIT_SPACE, CONFIG_SPACE);
+    }
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Admin-configured bounds on offer sizes for a deposit mint.
+#[account]
+#[derive(InitSpace)]
+pub struct MintLimits {
+    pub mint: Pubkey,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub bump: u8,
+}
+
+impl MintLimits {
+    /// Loads the limits PDA if the admin has created it; mints without one are unbounded.
+    pub fn load(info: &AccountInfo) -> Result<Option<MintLimits>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Some(MintLimits::try_deserialize(&mut &data[..])?))
+    }
+
+    pub fn check_deposit(&self, amount: u64) -> Result<()> {
+        require!(amount >= self.min_amount, ErrorCode::AmountTooSmall);
+        require!(amount <= self.max_amount, ErrorCode::AmountTooLarge);
+        Ok(())
+    }
+
+    /// Largest fill out of `deposited` that is at most `wanted` and does not
+    /// leave a residual below the minimum offer size.
+    pub fn max_fill(&self, deposited: u64, wanted: u64) -> u64 {
+        let fill = deposited.min(wanted);
+        let residual = deposited - fill;
+        if residual > 0 && residual < self.min_amount {
+            deposited.saturating_sub(self.min_amount)
+        } else {
+            fill
+        }
+    }
+}
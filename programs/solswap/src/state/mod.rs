@@ -3,3 +3,36 @@ pub use offer::*;
 
 mod config;
 pub use config::*;
+
+mod quote;
+pub use quote::*;
+
+mod mint_limits;
+pub use mint_limits::*;
+
+mod delegate;
+pub use delegate::*;
+
+mod stats;
+pub use stats::*;
+
+mod breaker;
+pub use breaker::*;
+
+mod vesting;
+pub use vesting::*;
+
+mod basket;
+pub use basket::*;
+
+mod ladder;
+pub use ladder::*;
+
+mod trader;
+pub use trader::*;
+
+mod commitment;
+pub use commitment::*;
+
+mod legacy;
+pub use legacy::*;
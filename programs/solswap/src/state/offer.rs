@@ -1,12 +1,855 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    bps_of, error::ErrorCode, lamports_to_usdc, usdc_to_lamports, verify_merkle_proof, FeeRates,
+    MintLimits, Quote, VestingParams, BPS_DENOMINATOR, WSOL_MINT,
+};
+
+pub const OFFER_VERSION: u8 = 3;
+pub const OFFER_RESERVED_SPACE: usize = 17;
+pub const MAX_MEMO_LEN: usize = 32;
+/// Byte offset of `client_order_id` in the account data, for memcmp filters
+#[constant]
+pub const OFFER_CLIENT_ORDER_ID_OFFSET: u64 = 8 + 1 + 32 + 32 + 8 + 32 + 32 + 1;
 
 #[account]
 #[derive(InitSpace)]
 pub struct Offer {
+    pub version: u8,
     pub token_mint_in: Pubkey,
     pub token_mint_out: Pubkey,
     pub deposited_amount: u64,
     pub depositor: Pubkey,
     pub vault: Pubkey,
     pub bump: u8,
+    /// Caller-chosen id; kept ahead of the variable-size fields so it can be filtered on
+    pub client_order_id: u128,
+    pub auction: Option<Auction>,
+    pub trigger: Option<Trigger>,
+    pub dca: Option<Dca>,
+    #[max_len(MAX_MEMO_LEN)]
+    pub memo: String,
+    /// Exact amount of `token_mint_out` wanted for the whole deposit. Barter
+    /// offers are settled with `take_barter`, without the oracle or mint allowlist.
+    pub barter_amount_out: Option<u64>,
+    /// Tokens bought by takers are escrowed and released on this schedule
+    pub vesting: Option<VestingParams>,
+    /// NFT whose holder receives the proceeds and may cancel, once tokenized
+    pub position_mint: Option<Pubkey>,
+    /// Wallet paid instead of the depositor, e.g. a treasury or exchange deposit address
+    pub proceeds_recipient: Option<Pubkey>,
+    /// Fills are reposted as the reverse offer instead of paying the depositor
+    pub grid: Option<Grid>,
+    /// Only takers whose `keccak(pubkey)` is a leaf of this tree can fill
+    pub taker_merkle_root: Option<[u8; 32]>,
+    /// Only holders of at least `min_amount` of `mint` can fill
+    pub taker_gate: Option<TokenGate>,
+    /// Slot the offer was created or its terms last changed in; unrevealed
+    /// commitments made before it get their bond back instead of forfeiting it
+    pub updated_slot: u64,
+    /// Zeroed space new fields are carved from without reallocating
+    pub reserved: [u8; OFFER_RESERVED_SPACE],
+}
+
+/// How the price of a Dutch auction offer moves over its duration.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AuctionPricing {
+    /// Starts at `start_premium_bps` above the oracle and decays linearly to
+    /// `end_discount_bps` below it.
+    OracleRelative {
+        start_premium_bps: u16,
+        end_discount_bps: u16,
+    },
+    /// Decays linearly between two SOL/USD prices, in the oracle's units.
+    Absolute { start_price: i64, end_price: i64 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct AuctionParams {
+    pub pricing: AuctionPricing,
+    pub duration: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Auction {
+    pub pricing: AuctionPricing,
+    pub start_time: i64,
+    pub duration: i64,
+}
+
+/// Side of the threshold the oracle price must reach to arm a trigger offer.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum TriggerDirection {
+    Above,
+    Below,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct TriggerParams {
+    /// SOL/USD threshold in the oracle's units
+    pub price: i64,
+    pub direction: TriggerDirection,
+    /// Lamports escrowed in the offer and paid to the keeper that arms it
+    pub bounty: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Trigger {
+    pub price: i64,
+    pub direction: TriggerDirection,
+    pub bounty: u64,
+    pub armed: bool,
+}
+
+impl Trigger {
+    pub fn is_met(&self, oracle_price: i64) -> bool {
+        match self.direction {
+            TriggerDirection::Above => oracle_price >= self.price,
+            TriggerDirection::Below => oracle_price <= self.price,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct DcaParams {
+    pub tranche_size: u64,
+    /// Seconds between tranches
+    pub interval: i64,
+    /// Worst SOL/USD oracle price the depositor accepts, in the oracle's units
+    pub limit_price: Option<i64>,
+    /// Premium over the oracle amount the taker pays, in bps
+    pub spread_bps: u16,
+}
+
+/// Releases the deposit in fixed tranches, one per interval.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Dca {
+    pub tranche_size: u64,
+    pub interval: i64,
+    pub next_available_at: i64,
+    pub limit_price: Option<i64>,
+    pub spread_bps: u16,
+}
+
+/// Holding required of takers, e.g. a community token or a collection NFT mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct TokenGate {
+    pub mint: Pubkey,
+    pub min_amount: u64,
+}
+
+/// Fixed-price offer whose proceeds become the depositor's offer on the other
+/// side of the pair, one step away, for a bounded number of cycles.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Grid {
+    /// SOL/USD price the offer fills at, in the oracle's units
+    pub price: i64,
+    /// Gap between the buy and sell levels, in bps of the buy price
+    pub step_bps: u16,
+    /// Side flips left; the last offer of the chain pays the depositor
+    pub cycles_left: u16,
+}
+
+impl Grid {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.price > 0 && self.step_bps > 0 && self.cycles_left > 0,
+            ErrorCode::InvalidGrid
+        );
+        Ok(())
+    }
+
+    /// Grid of the reverse offer fed by a fill on this one. Sells sit one
+    /// step above buys, so the two sides alternate between the same prices.
+    pub fn reverse(&self, sold_sol: bool) -> Result<Grid> {
+        let step = BPS_DENOMINATOR as i128 + self.step_bps as i128;
+        let price = if sold_sol {
+            self.price as i128 * BPS_DENOMINATOR as i128 / step
+        } else {
+            self.price as i128 * step / BPS_DENOMINATOR as i128
+        };
+        Ok(Grid {
+            price: i64::try_from(price).map_err(|_| ErrorCode::MathOverflow)?,
+            step_bps: self.step_bps,
+            cycles_left: self.cycles_left - 1,
+        })
+    }
+}
+
+impl DcaParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.tranche_size > 0 && self.interval > 0,
+            ErrorCode::InvalidDca
+        );
+        if let Some(limit_price) = self.limit_price {
+            require!(limit_price > 0, ErrorCode::InvalidDca);
+        }
+        Ok(())
+    }
+}
+
+impl AuctionParams {
+    /// `depositor_gave_usdc` fixes which way an absolute price must move for
+    /// the taker's cost to decay: takers of SOL offers pay `lamports * price`
+    /// so the price must fall, and takers of USDC offers pay `usdc / price`
+    /// so it must rise.
+    pub fn validate(&self, depositor_gave_usdc: bool) -> Result<()> {
+        require!(self.duration > 0, ErrorCode::InvalidAuction);
+        match self.pricing {
+            AuctionPricing::OracleRelative {
+                end_discount_bps, ..
+            } => require!(
+                end_discount_bps < BPS_DENOMINATOR as u16,
+                ErrorCode::InvalidAuction
+            ),
+            AuctionPricing::Absolute {
+                start_price,
+                end_price,
+            } => {
+                require!(start_price > 0 && end_price > 0, ErrorCode::InvalidAuction);
+                if depositor_gave_usdc {
+                    require!(start_price <= end_price, ErrorCode::InvalidAuction);
+                } else {
+                    require!(start_price >= end_price, ErrorCode::InvalidAuction);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Auction {
+    /// Seconds elapsed since the auction started, clamped to its duration.
+    fn elapsed(&self, now: i64) -> i64 {
+        (now - self.start_time).clamp(0, self.duration)
+    }
+
+    /// Absolute price at `now`, or `None` for oracle-relative auctions.
+    pub fn absolute_price(&self, now: i64) -> Option<i64> {
+        match self.pricing {
+            AuctionPricing::Absolute {
+                start_price,
+                end_price,
+            } => {
+                let elapsed = self.elapsed(now) as i128;
+                let delta =
+                    (end_price as i128 - start_price as i128) * elapsed / self.duration as i128;
+                Some((start_price as i128 + delta) as i64)
+            }
+            AuctionPricing::OracleRelative { .. } => None,
+        }
+    }
+
+    /// Adjustment applied to the oracle-priced amount at `now`, in bps.
+    pub fn adjustment_bps(&self, now: i64) -> i64 {
+        match self.pricing {
+            AuctionPricing::OracleRelative {
+                start_premium_bps,
+                end_discount_bps,
+            } => {
+                let range = start_premium_bps as i64 + end_discount_bps as i64;
+                start_premium_bps as i64 - range * self.elapsed(now) / self.duration
+            }
+            AuctionPricing::Absolute { .. } => 0,
+        }
+    }
+}
+
+impl Offer {
+    pub fn depositor_gave_usdc(&self) -> bool {
+        self.token_mint_in != WSOL_MINT
+    }
+
+    /// Checks the offer still vests on the schedule the taker agreed to, which
+    /// the depositor could otherwise change in front of the take.
+    pub fn check_vesting(&self, expected: Option<VestingParams>) -> Result<()> {
+        require!(self.vesting == expected, ErrorCode::VestingMismatch);
+        Ok(())
+    }
+
+    /// Checks `taker` is on the offer's allowlist, if it has one, using a
+    /// proof of its leaf against `taker_merkle_root`.
+    pub fn check_taker_allowed(&self, taker: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+        if let Some(root) = self.taker_merkle_root {
+            let leaf = keccak::hash(taker.as_ref()).to_bytes();
+            require!(
+                verify_merkle_proof(proof, root, leaf),
+                ErrorCode::TakerNotAllowed
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks `taker` meets the offer's token gate, if it has one, with a
+    /// token account it owns.
+    pub fn check_taker_gate(
+        &self,
+        taker: &Pubkey,
+        gate_token_account: Option<&TokenAccount>,
+    ) -> Result<()> {
+        if let Some(gate) = self.taker_gate {
+            let account = gate_token_account.ok_or(ErrorCode::TakerNotGated)?;
+            require!(
+                account.owner == *taker
+                    && account.mint == gate.mint
+                    && account.amount >= gate.min_amount,
+                ErrorCode::TakerNotGated
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks allowlisted and token-gated offers pay out to the checked taker,
+    /// who could otherwise fill them on behalf of any `recipient`.
+    pub fn check_recipient(&self, taker: &Pubkey, recipient: &Pubkey) -> Result<()> {
+        if self.taker_merkle_root.is_some() || self.taker_gate.is_some() {
+            require_keys_eq!(*recipient, *taker, ErrorCode::RecipientNotTaker);
+        }
+        Ok(())
+    }
+
+    /// Whether the next fill's proceeds are reposted as the reverse offer.
+    pub fn reposts_proceeds(&self) -> bool {
+        self.grid.is_some_and(|grid| grid.cycles_left > 0)
+    }
+
+    /// Grid of the depositor's offer on the other side, fed by fills of this one.
+    fn reverse_grid(&self) -> Result<Grid> {
+        self.grid
+            .ok_or(ErrorCode::InvalidGrid)?
+            .reverse(!self.depositor_gave_usdc())
+    }
+
+    /// Empty offer on the other side of the pair that fills of this grid
+    /// offer are reposted into, with the same taker restrictions.
+    pub fn grid_reverse(&self, vault: Pubkey, bump: u8, slot: u64) -> Result<Offer> {
+        Ok(Offer {
+            version: OFFER_VERSION,
+            token_mint_in: self.token_mint_out,
+            token_mint_out: self.token_mint_in,
+            deposited_amount: 0,
+            depositor: self.depositor,
+            vault,
+            bump,
+            client_order_id: self.client_order_id,
+            auction: None,
+            trigger: None,
+            dca: None,
+            memo: self.memo.clone(),
+            barter_amount_out: None,
+            vesting: self.vesting,
+            position_mint: None,
+            proceeds_recipient: None,
+            grid: Some(self.reverse_grid()?),
+            taker_merkle_root: self.taker_merkle_root,
+            taker_gate: self.taker_gate,
+            updated_slot: slot,
+            reserved: [0; OFFER_RESERVED_SPACE],
+        })
+    }
+
+    /// Adds `amount` of proceeds from a fill of this grid offer to the
+    /// depositor's offer on the other side, which must stay within the
+    /// maximum offer size of `limits` for its `token_mint_in`.
+    pub fn repost_into(
+        &self,
+        reverse: &mut Offer,
+        amount: u64,
+        limits: Option<&MintLimits>,
+    ) -> Result<()> {
+        require!(reverse.grid.is_some(), ErrorCode::GridSlotTaken);
+
+        let deposited_amount = reverse
+            .deposited_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if let Some(limits) = limits {
+            require!(
+                deposited_amount <= limits.max_amount,
+                ErrorCode::AmountTooLarge
+            );
+        }
+
+        reverse.grid = Some(self.reverse_grid()?);
+        reverse.deposited_amount = deposited_amount;
+        Ok(())
+    }
+
+    /// Wallet takers pay when the offer is not tokenized.
+    pub fn proceeds_wallet(&self) -> Pubkey {
+        self.proceeds_recipient.unwrap_or(self.depositor)
+    }
+
+    /// Checks `owner` is entitled to the offer's proceeds: the holder of its
+    /// position NFT once tokenized, the proceeds wallet otherwise.
+    pub fn check_proceeds_owner(
+        &self,
+        owner: &Pubkey,
+        position_token_account: Option<&TokenAccount>,
+    ) -> Result<()> {
+        self.check_owner(owner, position_token_account, self.proceeds_wallet())
+    }
+
+    /// Same for refunds of the deposit, which go back to the depositor rather
+    /// than the proceeds recipient.
+    pub fn check_refund_owner(
+        &self,
+        owner: &Pubkey,
+        position_token_account: Option<&TokenAccount>,
+    ) -> Result<()> {
+        self.check_owner(owner, position_token_account, self.depositor)
+    }
+
+    fn check_owner(
+        &self,
+        owner: &Pubkey,
+        position_token_account: Option<&TokenAccount>,
+        untokenized_owner: Pubkey,
+    ) -> Result<()> {
+        match self.position_mint {
+            Some(position_mint) => {
+                let position = position_token_account.ok_or(ErrorCode::NotPositionHolder)?;
+                require!(
+                    position.mint == position_mint
+                        && position.owner == *owner
+                        && position.amount == 1,
+                    ErrorCode::NotPositionHolder
+                );
+            }
+            None => require_keys_eq!(*owner, untokenized_owner, ErrorCode::Unauthorized),
+        }
+        Ok(())
+    }
+
+    /// Whether a taker can fill the offer at the oracle price now: trigger
+    /// offers must have been armed and DCA offers need an unlocked tranche
+    /// within the depositor's limit. Barter offers are never oracle-priced.
+    pub fn is_fillable(&self, oracle_price: i64, now: i64) -> bool {
+        if self.barter_amount_out.is_some() {
+            return false;
+        }
+
+        let armed = match self.trigger {
+            Some(trigger) => trigger.armed,
+            None => true,
+        };
+        armed && self.within_limit(oracle_price) && self.available_amount(now) > 0
+    }
+
+    fn within_limit(&self, oracle_price: i64) -> bool {
+        match self.dca.and_then(|dca| dca.limit_price) {
+            // Buying SOL with USDC: the depositor wants the price at or below the limit
+            Some(limit) if self.depositor_gave_usdc() => oracle_price <= limit,
+            Some(limit) => oracle_price >= limit,
+            None => true,
+        }
+    }
+
+    /// Amount of `token_mint_in` a taker can fill right now.
+    pub fn available_amount(&self, now: i64) -> u64 {
+        match self.dca {
+            Some(dca) if now < dca.next_available_at => 0,
+            Some(dca) => dca.tranche_size.min(self.deposited_amount),
+            None => self.deposited_amount,
+        }
+    }
+
+    /// Books a fill of `amount` and, for DCA offers, locks until the next
+    /// interval boundary after `now`.
+    pub fn record_fill(&mut self, amount: u64, now: i64) {
+        self.deposited_amount -= amount;
+        if let Some(dca) = self.dca.as_mut() {
+            let missed = (now - dca.next_available_at) / dca.interval;
+            dca.next_available_at += (missed + 1) * dca.interval;
+        }
+    }
+
+    /// Combined auction adjustment and DCA spread applied on top of the price.
+    pub fn spread_bps(&self, now: i64) -> i64 {
+        self.auction
+            .map(|auction| auction.adjustment_bps(now))
+            .unwrap_or(0)
+            + self.dca.map(|dca| dca.spread_bps as i64).unwrap_or(0)
+    }
+
+    /// What a taker pays and receives for `amount` of `token_mint_in`, with
+    /// `referral_bps` of it going to a referrer and `fees` charged on top.
+    pub fn quote(
+        &self,
+        amount: u64,
+        oracle_price: i64,
+        now: i64,
+        referral_bps: u16,
+        fees: FeeRates,
+    ) -> Result<Quote> {
+        let amount_out = self.amount_out_for(amount, oracle_price, now)?;
+        let referral_amount = bps_of(amount, referral_bps);
+        let (taker_fee, maker_rebate) = fees.fees_on(amount_out);
+
+        Ok(Quote {
+            amount_in: amount,
+            amount_out,
+            referral_amount,
+            taker_receives: amount - referral_amount,
+            taker_fee,
+            maker_rebate,
+            oracle_price,
+            spread_bps: self.spread_bps(now),
+            timestamp: now,
+        })
+    }
+
+    /// USDC side of a fill, used to meter the pair's notional in either direction.
+    pub fn usdc_notional(&self, amount_in: u64, amount_out: u64) -> u64 {
+        if self.depositor_gave_usdc() {
+            amount_in
+        } else {
+            amount_out
+        }
+    }
+
+    /// Amount of `token_mint_out` the taker owes for `amount` of `token_mint_in`.
+    pub fn amount_out_for(&self, amount: u64, oracle_price: i64, now: i64) -> Result<u64> {
+        let price = self
+            .auction
+            .and_then(|auction| auction.absolute_price(now))
+            .or(self.grid.map(|grid| grid.price))
+            .unwrap_or(oracle_price);
+        require!(price > 0, ErrorCode::InvalidPrice);
+
+        let base = if self.depositor_gave_usdc() {
+            usdc_to_lamports(amount, price)
+        } else {
+            lamports_to_usdc(amount, price)
+        };
+
+        let adjustment = self.spread_bps(now);
+        let adjusted = (base as u128) * (BPS_DENOMINATOR as i64 + adjustment) as u128
+            / BPS_DENOMINATOR as u128;
+
+        u64::try_from(adjusted).map_err(|_| ErrorCode::MathOverflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token_2022::spl_token_2022::state::{Account, AccountState};
+
+    const GATE_MINT: Pubkey = Pubkey::new_from_array([7; 32]);
+
+    fn gated_offer(min_amount: u64) -> Offer {
+        Offer {
+            version: OFFER_VERSION,
+            token_mint_in: WSOL_MINT,
+            token_mint_out: Pubkey::new_unique(),
+            deposited_amount: 1_000,
+            depositor: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            bump: 255,
+            client_order_id: 0,
+            auction: None,
+            trigger: None,
+            dca: None,
+            memo: String::new(),
+            barter_amount_out: None,
+            vesting: None,
+            position_mint: None,
+            proceeds_recipient: None,
+            grid: None,
+            taker_merkle_root: None,
+            taker_gate: Some(TokenGate {
+                mint: GATE_MINT,
+                min_amount,
+            }),
+            updated_slot: 0,
+            reserved: [0; OFFER_RESERVED_SPACE],
+        }
+    }
+
+    fn token_account(owner: Pubkey, mint: Pubkey, amount: u64) -> TokenAccount {
+        let account = Account {
+            mint,
+            owner,
+            amount,
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        let mut data = vec![0; Account::LEN];
+        account.pack_into_slice(&mut data);
+        TokenAccount::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    #[test]
+    fn gate_accepts_a_holder_of_the_minimum() {
+        let taker = Pubkey::new_unique();
+        let account = token_account(taker, GATE_MINT, 100);
+
+        assert!(gated_offer(100)
+            .check_taker_gate(&taker, Some(&account))
+            .is_ok());
+    }
+
+    #[test]
+    fn gate_rejects_a_missing_account() {
+        let taker = Pubkey::new_unique();
+
+        assert_eq!(
+            gated_offer(100).check_taker_gate(&taker, None).unwrap_err(),
+            ErrorCode::TakerNotGated.into()
+        );
+    }
+
+    #[test]
+    fn gate_rejects_another_owners_account() {
+        let taker = Pubkey::new_unique();
+        let account = token_account(Pubkey::new_unique(), GATE_MINT, 100);
+
+        assert_eq!(
+            gated_offer(100)
+                .check_taker_gate(&taker, Some(&account))
+                .unwrap_err(),
+            ErrorCode::TakerNotGated.into()
+        );
+    }
+
+    #[test]
+    fn gate_rejects_another_mint() {
+        let taker = Pubkey::new_unique();
+        let account = token_account(taker, Pubkey::new_unique(), 100);
+
+        assert_eq!(
+            gated_offer(100)
+                .check_taker_gate(&taker, Some(&account))
+                .unwrap_err(),
+            ErrorCode::TakerNotGated.into()
+        );
+    }
+
+    #[test]
+    fn gate_rejects_a_balance_below_the_minimum() {
+        let taker = Pubkey::new_unique();
+        let account = token_account(taker, GATE_MINT, 99);
+
+        assert_eq!(
+            gated_offer(100)
+                .check_taker_gate(&taker, Some(&account))
+                .unwrap_err(),
+            ErrorCode::TakerNotGated.into()
+        );
+    }
+
+    #[test]
+    fn ungated_offers_ignore_the_gate_account() {
+        let mut offer = gated_offer(100);
+        offer.taker_gate = None;
+
+        assert!(offer.check_taker_gate(&Pubkey::new_unique(), None).is_ok());
+    }
+
+    #[test]
+    fn gated_and_allowlisted_offers_pay_only_the_taker() {
+        let taker = Pubkey::new_unique();
+        let proxy = Pubkey::new_unique();
+        let mut offer = gated_offer(100);
+
+        assert!(offer.check_recipient(&taker, &taker).is_ok());
+        assert_eq!(
+            offer.check_recipient(&taker, &proxy).unwrap_err(),
+            ErrorCode::RecipientNotTaker.into()
+        );
+
+        offer.taker_gate = None;
+        offer.taker_merkle_root = Some([1; 32]);
+        assert_eq!(
+            offer.check_recipient(&taker, &proxy).unwrap_err(),
+            ErrorCode::RecipientNotTaker.into()
+        );
+
+        offer.taker_merkle_root = None;
+        assert!(offer.check_recipient(&taker, &proxy).is_ok());
+    }
+
+    const START: i64 = 1_000;
+    // SOL/USD prices with the oracle's 8 decimals
+    const SOL_USD_100: i64 = 10_000_000_000;
+    const SOL_USD_150: i64 = 15_000_000_000;
+    const SOL_USD_200: i64 = 20_000_000_000;
+
+    fn absolute(start_price: i64, end_price: i64) -> AuctionPricing {
+        AuctionPricing::Absolute {
+            start_price,
+            end_price,
+        }
+    }
+
+    fn oracle_relative() -> AuctionPricing {
+        AuctionPricing::OracleRelative {
+            start_premium_bps: 500,
+            end_discount_bps: 300,
+        }
+    }
+
+    fn auction(pricing: AuctionPricing) -> Auction {
+        Auction {
+            pricing,
+            start_time: START,
+            duration: 100,
+        }
+    }
+
+    fn auctioned_offer(pricing: AuctionPricing, token_mint_in: Pubkey) -> Offer {
+        let mut offer = gated_offer(0);
+        offer.taker_gate = None;
+        offer.token_mint_in = token_mint_in;
+        offer.auction = Some(auction(pricing));
+        offer
+    }
+
+    #[test]
+    fn absolute_prices_must_decay_for_the_taker() {
+        let params = |pricing| AuctionParams {
+            pricing,
+            duration: 100,
+        };
+
+        // SOL offers: the taker pays lamports * price, so the price falls
+        assert!(params(absolute(SOL_USD_200, SOL_USD_100))
+            .validate(false)
+            .is_ok());
+        assert_eq!(
+            params(absolute(SOL_USD_100, SOL_USD_200))
+                .validate(false)
+                .unwrap_err(),
+            ErrorCode::InvalidAuction.into()
+        );
+
+        // USDC offers: the taker pays usdc / price, so the price rises
+        assert!(params(absolute(SOL_USD_100, SOL_USD_200))
+            .validate(true)
+            .is_ok());
+        assert_eq!(
+            params(absolute(SOL_USD_200, SOL_USD_100))
+                .validate(true)
+                .unwrap_err(),
+            ErrorCode::InvalidAuction.into()
+        );
+
+        assert!(params(oracle_relative()).validate(true).is_ok());
+    }
+
+    #[test]
+    fn absolute_price_interpolates_over_the_auction() {
+        let auction = auction(absolute(SOL_USD_200, SOL_USD_100));
+
+        assert_eq!(auction.absolute_price(START), Some(SOL_USD_200));
+        assert_eq!(auction.absolute_price(START + 50), Some(SOL_USD_150));
+        assert_eq!(auction.absolute_price(START + 100), Some(SOL_USD_100));
+        assert_eq!(auction.absolute_price(START + 1_000), Some(SOL_USD_100));
+        assert_eq!(auction.absolute_price(START - 10), Some(SOL_USD_200));
+        assert_eq!(self::auction(oracle_relative()).absolute_price(START), None);
+    }
+
+    #[test]
+    fn adjustment_moves_from_premium_to_discount() {
+        let auction = auction(oracle_relative());
+
+        assert_eq!(auction.adjustment_bps(START), 500);
+        assert_eq!(auction.adjustment_bps(START + 50), 100);
+        assert_eq!(auction.adjustment_bps(START + 100), -300);
+        assert_eq!(auction.adjustment_bps(START + 1_000), -300);
+        assert_eq!(
+            self::auction(absolute(SOL_USD_200, SOL_USD_100)).adjustment_bps(START),
+            0
+        );
+    }
+
+    #[test]
+    fn absolute_auction_amount_out_decays_for_sol_offers() {
+        let offer = auctioned_offer(absolute(SOL_USD_200, SOL_USD_100), WSOL_MINT);
+        let one_sol = 1_000_000_000;
+
+        // The oracle price is ignored while an absolute auction runs
+        assert_eq!(
+            offer.amount_out_for(one_sol, SOL_USD_100, START).unwrap(),
+            200_000_000
+        );
+        assert_eq!(
+            offer
+                .amount_out_for(one_sol, SOL_USD_100, START + 50)
+                .unwrap(),
+            150_000_000
+        );
+        assert_eq!(
+            offer
+                .amount_out_for(one_sol, SOL_USD_100, START + 100)
+                .unwrap(),
+            100_000_000
+        );
+    }
+
+    #[test]
+    fn relative_auction_amount_out_decays_for_usdc_offers() {
+        let offer = auctioned_offer(oracle_relative(), Pubkey::new_unique());
+        let hundred_usdc = 100_000_000;
+
+        assert_eq!(
+            offer
+                .amount_out_for(hundred_usdc, SOL_USD_100, START)
+                .unwrap(),
+            1_050_000_000
+        );
+        assert_eq!(
+            offer
+                .amount_out_for(hundred_usdc, SOL_USD_100, START + 50)
+                .unwrap(),
+            1_010_000_000
+        );
+        assert_eq!(
+            offer
+                .amount_out_for(hundred_usdc, SOL_USD_100, START + 100)
+                .unwrap(),
+            970_000_000
+        );
+    }
+
+    #[test]
+    fn repost_respects_the_reverse_mint_maximum() {
+        let mut offer = gated_offer(0);
+        offer.taker_gate = None;
+        offer.grid = Some(Grid {
+            price: SOL_USD_100,
+            step_bps: 100,
+            cycles_left: 2,
+        });
+        let mut reverse = offer.grid_reverse(Pubkey::new_unique(), 255, 0).unwrap();
+        reverse.deposited_amount = 600;
+        let limits = MintLimits {
+            mint: reverse.token_mint_in,
+            min_amount: 0,
+            max_amount: 1_000,
+            bump: 255,
+        };
+
+        assert_eq!(
+            offer
+                .repost_into(&mut reverse, 401, Some(&limits))
+                .unwrap_err(),
+            ErrorCode::AmountTooLarge.into()
+        );
+        assert_eq!(reverse.deposited_amount, 600);
+
+        assert!(offer.repost_into(&mut reverse, 400, Some(&limits)).is_ok());
+        assert_eq!(reverse.deposited_amount, 1_000);
+        assert_eq!(reverse.grid.unwrap().cycles_left, 1);
+
+        assert_eq!(
+            offer.repost_into(&mut reverse, u64::MAX, None).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
+    }
 }
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, NONCES_PER_PAGE};
+
+/// An off-chain RFQ quote signed by the maker with their ed25519 key.
+///
+/// The maker gives `amount_in` of `mint_in` and receives `amount_out` of
+/// `mint_out` from `taker`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignedQuote {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub expiry: i64,
+    pub nonce: u64,
+}
+
+impl SignedQuote {
+    /// Bytes the maker signs: a domain tag followed by the borsh-encoded quote.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = crate::QUOTE_DOMAIN.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+
+    pub fn nonce_page(&self) -> u64 {
+        self.nonce / NONCES_PER_PAGE
+    }
+}
+
+/// What `take_offer` would charge for a fill, as returned by `quote_take`.
+///
+/// The taker pays `amount_out` plus `taker_fee` of `token_mint_out` and
+/// receives `taker_receives` of `token_mint_in`; the rest of `amount_in` goes
+/// to the referrer. `maker_rebate` of the fee goes to the depositor.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub referral_amount: u64,
+    pub taker_receives: u64,
+    pub taker_fee: u64,
+    pub maker_rebate: u64,
+    pub oracle_price: i64,
+    pub spread_bps: i64,
+    pub timestamp: i64,
+}
+
+/// Bitmap of used quote nonces for a maker, one page per `NONCES_PER_PAGE` nonces.
+#[account]
+#[derive(InitSpace)]
+pub struct QuoteNonces {
+    pub maker: Pubkey,
+    pub page: u64,
+    pub bits: [u8; (NONCES_PER_PAGE / 8) as usize],
+    pub bump: u8,
+}
+
+impl QuoteNonces {
+    /// Marks `nonce` as used, failing if it has already been consumed.
+    pub fn consume(&mut self, nonce: u64) -> Result<()> {
+        let index = (nonce % NONCES_PER_PAGE) as usize;
+        let (byte, mask) = (index / 8, 1u8 << (index % 8));
+        require!(self.bits[byte] & mask == 0, ErrorCode::QuoteNonceUsed);
+        self.bits[byte] |= mask;
+        Ok(())
+    }
+}
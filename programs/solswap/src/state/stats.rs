@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+/// Running totals for offers selling `token_mint_in` for `token_mint_out`.
+#[account]
+#[derive(InitSpace)]
+pub struct Stats {
+    pub token_mint_in: Pubkey,
+    pub token_mint_out: Pubkey,
+    pub offers_created: u64,
+    /// Number of fills, including partial ones
+    pub offers_filled: u64,
+    pub offers_cancelled: u64,
+    pub volume_in: u128,
+    pub volume_out: u128,
+    /// Amount of `token_mint_in` currently escrowed in open offers
+    pub escrowed: u64,
+    /// Oracle SOL/USD price at the last fill, in the oracle's units
+    pub last_fill_price: i64,
+    pub last_fill_timestamp: i64,
+    pub bump: u8,
+}
+
+impl Stats {
+    /// Sets the pair on a freshly created stats account.
+    pub fn init_if_empty(&mut self, token_mint_in: Pubkey, token_mint_out: Pubkey, bump: u8) {
+        if self.token_mint_in == Pubkey::default() {
+            self.token_mint_in = token_mint_in;
+            self.token_mint_out = token_mint_out;
+            self.bump = bump;
+        }
+    }
+
+    pub fn record_created(&mut self, amount: u64) {
+        self.offers_created += 1;
+        self.escrowed = self.escrowed.saturating_add(amount);
+    }
+
+    pub fn record_fill(&mut self, amount_in: u64, amount_out: u64, price: i64, now: i64) {
+        self.record_volume(amount_in, amount_out);
+        self.last_fill_price = price;
+        self.last_fill_timestamp = now;
+    }
+
+    /// Books a fill that was not priced by the oracle, keeping the last fill price.
+    pub fn record_volume(&mut self, amount_in: u64, amount_out: u64) {
+        self.offers_filled += 1;
+        self.volume_in += amount_in as u128;
+        self.volume_out += amount_out as u128;
+        // Offers created before stats existed were never counted in `escrowed`
+        self.escrowed = self.escrowed.saturating_sub(amount_in);
+    }
+
+    pub fn record_amended(&mut self, old_amount: u64, new_amount: u64) {
+        self.escrowed = self
+            .escrowed
+            .saturating_add(new_amount)
+            .saturating_sub(old_amount);
+    }
+
+    pub fn record_cancelled(&mut self, amount: u64) {
+        self.offers_cancelled += 1;
+        self.escrowed = self.escrowed.saturating_sub(amount);
+    }
+}
@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+
+pub const VOLUME_WINDOW_DAYS: usize = 30;
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Rolling USDC volume of a wallet across its takes and filled offers,
+/// bucketed by day, used to pick its fee tier.
+#[account]
+#[derive(InitSpace)]
+pub struct TraderAccount {
+    pub wallet: Pubkey,
+    /// Volume per day, indexed by day number modulo `VOLUME_WINDOW_DAYS`
+    pub daily_volume: [u64; VOLUME_WINDOW_DAYS],
+    /// Day number of the most recent bucket written
+    pub last_day: i64,
+    pub bump: u8,
+}
+
+impl TraderAccount {
+    /// Sets the wallet on a freshly created trader account.
+    pub fn init_if_empty(&mut self, wallet: Pubkey, bump: u8) {
+        if self.wallet == Pubkey::default() {
+            self.wallet = wallet;
+            self.bump = bump;
+        }
+    }
+
+    fn bucket(day: i64) -> usize {
+        day.rem_euclid(VOLUME_WINDOW_DAYS as i64) as usize
+    }
+
+    /// Volume over the `VOLUME_WINDOW_DAYS` days ending today.
+    pub fn rolling_volume(&self, now: i64) -> u64 {
+        let elapsed = (now / SECONDS_PER_DAY - self.last_day).max(0) as usize;
+        // The buckets of the oldest `elapsed` days have fallen out of the window
+        (0..VOLUME_WINDOW_DAYS.saturating_sub(elapsed))
+            .map(|age| self.daily_volume[Self::bucket(self.last_day - age as i64)])
+            .fold(0, u64::saturating_add)
+    }
+
+    pub fn record_volume(&mut self, notional: u64, now: i64) {
+        let today = now / SECONDS_PER_DAY;
+        if today > self.last_day {
+            // Clear the buckets of the days skipped since the last write
+            let first = today
+                .saturating_sub(VOLUME_WINDOW_DAYS as i64 - 1)
+                .max(self.last_day + 1);
+            for day in first..=today {
+                self.daily_volume[Self::bucket(day)] = 0;
+            }
+            self.last_day = today;
+        }
+        let bucket = &mut self.daily_volume[Self::bucket(self.last_day)];
+        *bucket = bucket.saturating_add(notional);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trader() -> TraderAccount {
+        TraderAccount {
+            wallet: Pubkey::new_unique(),
+            daily_volume: [0; VOLUME_WINDOW_DAYS],
+            last_day: 0,
+            bump: 255,
+        }
+    }
+
+    fn day(n: i64) -> i64 {
+        n * SECONDS_PER_DAY
+    }
+
+    #[test]
+    fn same_day_volume_accumulates() {
+        let mut trader = trader();
+        trader.record_volume(100, day(3));
+        trader.record_volume(50, day(3) + 3_600);
+
+        assert_eq!(trader.last_day, 3);
+        assert_eq!(trader.rolling_volume(day(3)), 150);
+    }
+
+    #[test]
+    fn days_leave_the_window_after_thirty_days() {
+        let mut trader = trader();
+        trader.record_volume(100, day(0));
+        trader.record_volume(10, day(1));
+
+        assert_eq!(trader.rolling_volume(day(1)), 110);
+        assert_eq!(trader.rolling_volume(day(29)), 110);
+        assert_eq!(trader.rolling_volume(day(30)), 10);
+        assert_eq!(trader.rolling_volume(day(31)), 0);
+    }
+
+    #[test]
+    fn rollover_clears_the_reused_bucket() {
+        let mut trader = trader();
+        trader.record_volume(100, day(0));
+        // Day 30 maps to the same bucket as day 0
+        trader.record_volume(7, day(30));
+
+        assert_eq!(trader.daily_volume[0], 7);
+        assert_eq!(trader.rolling_volume(day(30)), 7);
+    }
+
+    #[test]
+    fn gaps_clear_only_the_days_that_left_the_window() {
+        let mut trader = trader();
+        for n in 0..VOLUME_WINDOW_DAYS as i64 {
+            trader.record_volume(1, day(n));
+        }
+        assert_eq!(trader.rolling_volume(day(29)), 30);
+
+        // Days 16 to 29 are still in the window ending on day 45
+        trader.record_volume(5, day(45));
+        assert_eq!(trader.rolling_volume(day(45)), 19);
+
+        trader.record_volume(5, day(100));
+        assert_eq!(trader.daily_volume.iter().sum::<u64>(), 5);
+        assert_eq!(trader.rolling_volume(day(100)), 5);
+    }
+
+    #[test]
+    fn volume_saturates() {
+        let mut trader = trader();
+        trader.record_volume(u64::MAX, day(0));
+        trader.record_volume(1, day(0));
+        trader.record_volume(1, day(1));
+
+        assert_eq!(trader.rolling_volume(day(1)), u64::MAX);
+    }
+}
@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, MAX_VESTING_DURATION};
+
+/// Release schedule for tokens bought from an offer, relative to the fill.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct VestingParams {
+    /// Seconds after the fill before anything can be claimed
+    pub cliff: i64,
+    /// Seconds after the fill until everything is claimable; release is linear
+    pub duration: i64,
+}
+
+impl VestingParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.duration > 0
+                && self.duration <= MAX_VESTING_DURATION
+                && self.cliff >= 0
+                && self.cliff <= self.duration,
+            ErrorCode::InvalidVesting
+        );
+        Ok(())
+    }
+}
+
+/// Tokens a taker bought from `offer`, held in this PDA's ATA until they vest.
+///
+/// Later fills of the same offer by the same taker restart the schedule for
+/// everything not vested yet, so topping up cannot skip the wait. The offer
+/// PDA is reused across offers, so the escrow keeps the longest cliff and
+/// duration it has been topped up under.
+#[account]
+#[derive(InitSpace)]
+pub struct VestingEscrow {
+    pub beneficiary: Pubkey,
+    pub offer: Pubkey,
+    pub mint: Pubkey,
+    pub total: u64,
+    pub claimed: u64,
+    pub start: i64,
+    /// Amount already vested when the schedule last restarted at `start`
+    pub vested_at_start: u64,
+    pub cliff: i64,
+    pub duration: i64,
+    pub bump: u8,
+}
+
+impl VestingEscrow {
+    /// Amount released by `now`, claimed or not.
+    pub fn vested(&self, now: i64) -> u64 {
+        let elapsed = now - self.start;
+        if elapsed < self.cliff {
+            self.vested_at_start
+        } else if elapsed >= self.duration {
+            self.total
+        } else {
+            let unvested = (self.total - self.vested_at_start) as u128;
+            self.vested_at_start + (unvested * elapsed as u128 / self.duration as u128) as u64
+        }
+    }
+
+    /// Adds `amount` from a new fill under the offer's current `schedule` and
+    /// restarts the schedule at `now` for everything not vested yet. The
+    /// stored schedule can be lengthened but never shortened.
+    pub fn top_up(&mut self, amount: u64, schedule: VestingParams, now: i64) {
+        self.vested_at_start = self.vested(now);
+        self.total += amount;
+        self.start = now;
+        self.cliff = self.cliff.max(schedule.cliff);
+        self.duration = self.duration.max(schedule.duration);
+    }
+
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.vested(now) - self.claimed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escrow(cliff: i64, duration: i64) -> VestingEscrow {
+        VestingEscrow {
+            beneficiary: Pubkey::new_unique(),
+            offer: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            total: 0,
+            claimed: 0,
+            start: 1_000,
+            vested_at_start: 0,
+            cliff,
+            duration,
+            bump: 255,
+        }
+    }
+
+    fn schedule(escrow: &VestingEscrow) -> VestingParams {
+        VestingParams {
+            cliff: escrow.cliff,
+            duration: escrow.duration,
+        }
+    }
+
+    #[test]
+    fn vests_linearly_after_the_cliff() {
+        let mut escrow = escrow(100, 1_000);
+        escrow.top_up(1_000, schedule(&escrow), 1_000);
+
+        assert_eq!(escrow.vested(1_000), 0);
+        assert_eq!(escrow.vested(1_099), 0);
+        assert_eq!(escrow.vested(1_100), 100);
+        assert_eq!(escrow.vested(1_500), 500);
+        assert_eq!(escrow.vested(2_000), 1_000);
+        assert_eq!(escrow.vested(9_000), 1_000);
+    }
+
+    #[test]
+    fn rounds_vested_amount_down() {
+        let mut escrow = escrow(0, 3);
+        escrow.top_up(10, schedule(&escrow), 1_000);
+
+        assert_eq!(escrow.vested(1_001), 3);
+        assert_eq!(escrow.vested(1_002), 6);
+        assert_eq!(escrow.vested(1_003), 10);
+    }
+
+    #[test]
+    fn claimable_excludes_claimed() {
+        let mut escrow = escrow(0, 1_000);
+        escrow.top_up(1_000, schedule(&escrow), 1_000);
+        escrow.claimed = 300;
+
+        assert_eq!(escrow.claimable(1_500), 200);
+        assert_eq!(escrow.claimable(2_000), 700);
+    }
+
+    #[test]
+    fn top_up_restarts_the_schedule_for_unvested_tokens() {
+        let mut escrow = escrow(100, 1_000);
+        escrow.top_up(1_000, schedule(&escrow), 1_000);
+        escrow.top_up(1_000, schedule(&escrow), 1_500);
+
+        // What vested before the top-up stays vested, even during the new cliff
+        assert_eq!(escrow.vested_at_start, 500);
+        assert_eq!(escrow.vested(1_500), 500);
+        assert_eq!(escrow.vested(1_599), 500);
+        // The remaining 1_500 vest over a fresh duration
+        assert_eq!(escrow.vested(2_000), 500 + 750);
+        assert_eq!(escrow.vested(2_500), 2_000);
+    }
+
+    #[test]
+    fn top_up_after_full_vesting_does_not_release_new_tokens() {
+        let mut escrow = escrow(100, 1_000);
+        escrow.top_up(1, schedule(&escrow), 1_000);
+        escrow.top_up(1_000_000, schedule(&escrow), 5_000);
+
+        assert_eq!(escrow.vested(5_000), 1);
+        assert_eq!(escrow.vested(5_099), 1);
+        assert_eq!(escrow.vested(6_000), 1_000_001);
+    }
+
+    #[test]
+    fn top_up_applies_a_longer_schedule() {
+        let mut escrow = escrow(0, 1_000);
+        escrow.top_up(1_000, schedule(&escrow), 1_000);
+        let longer = VestingParams {
+            cliff: 500,
+            duration: 4_000,
+        };
+        escrow.top_up(1_000, longer, 1_500);
+
+        assert_eq!((escrow.cliff, escrow.duration), (500, 4_000));
+        assert_eq!(escrow.vested(1_999), 500);
+        assert_eq!(escrow.vested(2_000), 500 + 187);
+        assert_eq!(escrow.vested(5_500), 2_000);
+    }
+
+    #[test]
+    fn top_up_never_shortens_the_schedule() {
+        let mut escrow = escrow(500, 4_000);
+        escrow.top_up(1_000, schedule(&escrow), 1_000);
+        let shorter = VestingParams {
+            cliff: 0,
+            duration: 10,
+        };
+        escrow.top_up(1_000, shorter, 1_000);
+
+        assert_eq!((escrow.cliff, escrow.duration), (500, 4_000));
+        assert_eq!(escrow.vested(1_010), 0);
+    }
+}